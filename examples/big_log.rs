@@ -0,0 +1,83 @@
+use std::time::{Duration, Instant};
+
+use fltk::{
+    app,
+    prelude::{GroupExt, WidgetExt},
+    window::Window,
+};
+use rand::Rng;
+use simple_table::{
+    joe_table::JoeTable,
+    simple_model::{ColumnDetail, ColumnInfo, RowHeight, RowInfo, SimpleCell, SimpleModel},
+};
+
+const ROWS: usize = 1_000_000;
+const FRAMES: u32 = 200;
+
+/// Example model of a large log, where some lines are taller than others
+struct LogModel;
+
+impl SimpleModel for LogModel {
+    fn row_info(&mut self) -> RowInfo {
+        RowInfo {
+            count: ROWS,
+            height: RowHeight::PerRow(Box::new(|row| if row % 10 == 0 { 40 } else { 20 })),
+        }
+    }
+
+    fn column_info(&mut self) -> ColumnInfo {
+        ColumnInfo {
+            details: vec![
+                ColumnDetail {
+                    header: "Line".to_string(),
+                    width: 80,
+                },
+                ColumnDetail {
+                    header: "Message".to_string(),
+                    width: 200,
+                },
+            ],
+        }
+    }
+
+    fn get_cell(&mut self, row: i32, col: i32) -> SimpleCell {
+        match col {
+            0 => SimpleCell::Int(row as i64),
+            1 if row % 10 == 0 => SimpleCell::Text(format!("checkpoint {}\nall is well", row / 10)),
+            1 => SimpleCell::Text(format!("message {row}")),
+            _ => SimpleCell::None,
+        }
+    }
+}
+
+/// Demo of scrolling a million rows of different heights.  Prints the time to scroll to random rows
+/// and draw them, then leaves the table open to scroll by hand.  The row index itself is checked without
+/// a display by the million row test in `height_index.rs`.
+fn main() {
+    let app = app::App::default();
+    let mut wind = Window::default().with_size(300, 400).with_label("Big log");
+    let mut table = JoeTable::new(LogModel);
+    wind.resizable(&table.as_base_widget());
+    wind.end();
+    wind.show();
+
+    // the first draw measures every row
+    let start = Instant::now();
+    app::flush();
+    println!("first draw {:?}", start.elapsed());
+
+    let mut rng = rand::rng();
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..FRAMES {
+        let row = rng.random_range(0..ROWS);
+        let start = Instant::now();
+        table.scroll_to_row(row);
+        app::flush();
+        let visible = table.visible_rows();
+        elapsed += start.elapsed();
+        assert!(visible.contains(&row), "{row} not in {visible:?}");
+    }
+    println!("scroll and draw {:?} per frame", elapsed / FRAMES);
+
+    app.run().unwrap();
+}
@@ -0,0 +1,101 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use fltk::{app, prelude::*, window::Window};
+use rand::Rng;
+use simple_table::{
+    model_events::ModelEvents,
+    simple_model::{ColumnDetail, ColumnInfo, RowHeight, RowInfo, SimpleCell, SimpleModel},
+    simple_table::*,
+};
+
+/// Example model of counters, updated by another thread
+struct CounterModel {
+    counters: Arc<Mutex<Vec<u64>>>,
+    events: ModelEvents,
+}
+
+impl SimpleModel for CounterModel {
+    fn events(&mut self) -> Option<ModelEvents> {
+        Some(self.events.clone())
+    }
+
+    fn row_info(&mut self) -> RowInfo {
+        RowInfo {
+            count: self.counters.lock().unwrap().len(),
+            height: RowHeight::All(20),
+        }
+    }
+
+    fn column_info(&mut self) -> ColumnInfo {
+        ColumnInfo {
+            details: vec![
+                ColumnDetail {
+                    header: "Counter".to_string(),
+                    width: 80,
+                },
+                ColumnDetail {
+                    header: "Count".to_string(),
+                    width: 100,
+                },
+            ],
+        }
+    }
+
+    fn get_cell(&mut self, row: i32, col: i32) -> SimpleCell {
+        match col {
+            0 => SimpleCell::Int(row as i64),
+            1 => SimpleCell::Int(self.counters.lock().unwrap()[row as usize] as i64),
+            _ => SimpleCell::None,
+        }
+    }
+}
+
+/// demonstration of a model reporting its changes, so the table only repaints what changed.
+fn main() {
+    let counters = Arc::new(Mutex::new(vec![0; 20]));
+    let events = ModelEvents::new();
+
+    let app = app::App::default();
+    let mut wind = Window::default().with_size(200, 300).with_label("Events");
+    let mut table = SimpleTable::new(
+        fltk::table::Table::default_fill(),
+        CounterModel {
+            counters: counters.clone(),
+            events: events.clone(),
+        },
+    );
+    wind.resizable(&table.table);
+    wind.end();
+    wind.show();
+    assert!(table.redraw_on_events());
+
+    // count at random, and add a counter now and then
+    thread::spawn(move || {
+        let mut rng = rand::rng();
+        for tick in 1.. {
+            thread::sleep(Duration::from_millis(50));
+            if tick % 40 == 0 {
+                let row = {
+                    let mut counters = counters.lock().unwrap();
+                    counters.push(0);
+                    counters.len() - 1
+                };
+                events.rows_inserted(row..row + 1);
+            } else {
+                let row = {
+                    let mut counters = counters.lock().unwrap();
+                    let row = rng.random_range(0..counters.len());
+                    counters[row] += 1;
+                    row
+                };
+                events.rows_updated(row..row + 1);
+            }
+        }
+    });
+
+    app.run().unwrap();
+}
@@ -0,0 +1,68 @@
+use fltk::{app, group::Flex, input::Input, prelude::*, window::Window};
+use simple_table::{
+    filtered_model::FilteredModel,
+    simple_model::{ColumnDetail, ColumnInfo, RowHeight, RowInfo, SimpleCell, SimpleModel},
+    simple_table::*,
+};
+use timer::Timer;
+
+/// Example model, growing over time like a log of signals
+struct NumberModel {
+    start: std::time::Instant,
+}
+
+impl SimpleModel for NumberModel {
+    fn row_info(&mut self) -> RowInfo {
+        RowInfo {
+            count: 100 + self.start.elapsed().as_millis() as usize / 100,
+            height: RowHeight::All(20),
+        }
+    }
+
+    fn column_info(&mut self) -> ColumnInfo {
+        ColumnInfo {
+            details: vec![
+                ColumnDetail {
+                    header: "Number".to_string(),
+                    width: 80,
+                },
+                ColumnDetail {
+                    header: "Square".to_string(),
+                    width: 100,
+                },
+            ],
+        }
+    }
+
+    fn get_cell(&mut self, row: i32, col: i32) -> SimpleCell {
+        match col {
+            0 => SimpleCell::Text(row.to_string()),
+            1 => SimpleCell::Text((row as i64 * row as i64).to_string()),
+            _ => SimpleCell::None,
+        }
+    }
+}
+
+/// demonstration of a quick filter above a table.  Type a number to narrow the rows.
+fn main() {
+    let app = app::App::default();
+    let mut wind = Window::default().with_size(200, 300).with_label("Filter");
+    let mut flex = Flex::default_fill().column();
+    let mut input = Input::default();
+    flex.fixed(&input, 25);
+    let model = FilteredModel::new(NumberModel {
+        start: std::time::Instant::now(),
+    });
+    let mut table = SimpleTable::new(fltk::table::Table::default_fill(), model);
+    table.set_quick_filter(&mut input);
+    flex.end();
+    wind.resizable(&flex);
+    wind.end();
+    wind.show();
+
+    // rows keep arriving while filtered
+    let timer = Timer::new(); // requires variable, so that it isn't dropped.
+    table.redraw_on(&timer, chrono::Duration::milliseconds(200));
+
+    app.run().unwrap();
+}
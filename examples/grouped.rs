@@ -0,0 +1,55 @@
+use fltk::{app, prelude::*, window::Window};
+use simple_table::{
+    grouped_model::{Aggregate, GroupedModel},
+    simple_model::{SimpleCell, VecColumn, VecModel},
+    simple_table::*,
+};
+
+/// A signal, reported by an ECU at a source address
+struct Signal {
+    source: u8,
+    name: &'static str,
+    value: f64,
+    count: i64,
+}
+
+/// demonstration of rows grouped by source address.  Click a group to collapse it.
+fn main() {
+    let signal = |source, name, value, count| Signal {
+        source,
+        name,
+        value,
+        count,
+    };
+    let signals = vec![
+        signal(0, "Engine Speed", 1450.0, 120),
+        signal(3, "Output Shaft Speed", 980.5, 60),
+        signal(0, "Coolant Temperature", 88.0, 12),
+        signal(11, "Brake Pressure", 3.2, 40),
+        signal(3, "Current Gear", 5.0, 60),
+        signal(0, "Oil Pressure", 310.0, 12),
+    ];
+    let columns = vec![
+        VecColumn::new("Source", 80, |s: &Signal| SimpleCell::Int(s.source as i64)),
+        VecColumn::new("Name", 160, |s: &Signal| {
+            SimpleCell::Text(s.name.to_string())
+        }),
+        VecColumn::new("Value", 80, |s: &Signal| SimpleCell::Float {
+            value: s.value,
+            precision: 1,
+        }),
+        VecColumn::new("Count", 80, |s: &Signal| SimpleCell::Int(s.count)),
+    ];
+    let mut model = GroupedModel::new(VecModel::new(signals, columns));
+    model.set_group_by(&[0]);
+    model.set_aggregate(2, Aggregate::Max);
+    model.set_aggregate(3, Aggregate::Sum);
+
+    let app = app::App::default();
+    let mut wind = Window::default().with_size(420, 300).with_label("Grouped");
+    let table = SimpleTable::new(fltk::table::Table::default_fill(), model);
+    wind.resizable(&table.table);
+    wind.end();
+    wind.show();
+    app.run().unwrap();
+}
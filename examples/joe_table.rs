@@ -105,10 +105,17 @@ fn main() {
     // create an app with a scroll with a table of PersonModel
     let app = app::App::default();
     let mut wind = Window::default().with_size(200, 300).with_label("Counter");
-    let table = JoeTable::new(PersonModel {
+    let mut table = JoeTable::new(PersonModel {
         people,
         start: Instant::now(),
     });
+    // a detail pane would follow the selection here.  Ctrl+click selects more rows.
+    table.on_selection_changed(|selection| {
+        println!("selected {:?}", selection.selected_rows(usize::MAX))
+    });
+    // keep the names in view when scrolling sideways
+    table.freeze_columns(1);
+    table.set_row_header(true);
     wind.resizable(&table.as_base_widget());
     wind.end();
     wind.show();
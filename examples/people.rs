@@ -3,7 +3,7 @@ use std::time::Instant;
 use fltk::{app, prelude::*, window::Window};
 use fltk_theme::{SchemeType, WidgetScheme};
 use simple_table::{
-    simple_model::{Order, SimpleModel},
+    simple_model::{EditorKind, Order, SimpleModel},
     simple_table::*,
 };
 use timer::Timer;
@@ -21,7 +21,7 @@ struct PersonModel {
 }
 
 /// Example model implementation
-/// Just displays some names, then numbers.  Demonstrates a multiline cell, dynamically added cells, and sorting.
+/// Just displays some names, then numbers.  Demonstrates a multiline cell, dynamically added cells, sorting and editing.
 impl SimpleModel for PersonModel {
     fn sort(&mut self, col: usize, order: Order) {
         self.people.sort_by(|a, b| {
@@ -33,6 +33,30 @@ impl SimpleModel for PersonModel {
         });
     }
 
+    fn is_editable(&self, row: i32, col: i32) -> bool {
+        col == 1 && row < self.people.len() as i32
+    }
+
+    fn editor(&self, _row: i32, _col: i32) -> EditorKind {
+        EditorKind::Integer { min: 0, max: 150 }
+    }
+
+    fn set_cell(&mut self, row: i32, col: i32, value: &str) -> Result<(), String> {
+        let person = self
+            .people
+            .get_mut(row as usize)
+            .ok_or_else(|| format!("Row {row} can't be edited"))?;
+        match col {
+            1 => {
+                person.age = value
+                    .parse()
+                    .map_err(|_| format!("{value} is not an age"))?;
+                Ok(())
+            }
+            _ => Err("Only the age can be edited".to_string()),
+        }
+    }
+
     fn row_info(&mut self) -> simple_table::simple_model::RowInfo {
         let count = Instant::now().duration_since(self.start).as_millis() / 200;
         simple_table::simple_model::RowInfo {
@@ -0,0 +1,176 @@
+use std::collections::HashSet;
+
+use fltk::{app, prelude::*, window::Window};
+use simple_table::{
+    simple_model::{ColumnDetail, ColumnInfo, SimpleCell},
+    simple_table::*,
+    tree_model::{TreeModel, TreeTableModel},
+};
+
+/// A parameter, reported in a PGN
+struct Spn {
+    spn: u32,
+    name: &'static str,
+    value: f64,
+}
+
+/// A message, sent by an ECU
+struct Pgn {
+    pgn: u32,
+    name: &'static str,
+    /// loaded when the PGN is first expanded
+    spns: Option<Vec<Spn>>,
+}
+
+struct Ecu {
+    address: u8,
+    name: &'static str,
+    pgns: Vec<Pgn>,
+}
+
+/// Example tree of ECU → PGN → SPN.  Nodes are paths of indexes.
+struct J1939Tree {
+    ecus: Vec<Ecu>,
+    expanded: HashSet<Vec<usize>>,
+}
+
+impl J1939Tree {
+    fn pgn(&self, path: &[usize]) -> &Pgn {
+        &self.ecus[path[0]].pgns[path[1]]
+    }
+}
+
+impl TreeModel for J1939Tree {
+    type Node = Vec<usize>;
+
+    fn roots(&mut self) -> Vec<Vec<usize>> {
+        (0..self.ecus.len()).map(|e| vec![e]).collect()
+    }
+
+    fn children(&mut self, node: &Vec<usize>) -> Vec<Vec<usize>> {
+        let count = match node.len() {
+            1 => self.ecus[node[0]].pgns.len(),
+            2 => self.pgn(node).spns.as_ref().map_or(0, |s| s.len()),
+            _ => 0,
+        };
+        (0..count)
+            .map(|i| node.iter().copied().chain([i]).collect())
+            .collect()
+    }
+
+    fn has_children(&mut self, node: &Vec<usize>) -> bool {
+        // PGNs have SPNs before they are loaded
+        node.len() < 3
+    }
+
+    fn is_expanded(&self, node: &Vec<usize>) -> bool {
+        self.expanded.contains(node)
+    }
+
+    fn toggle(&mut self, node: &Vec<usize>) {
+        if !self.expanded.remove(node) {
+            self.expanded.insert(node.clone());
+        }
+    }
+
+    fn column_info(&mut self) -> ColumnInfo {
+        ColumnInfo {
+            details: vec![
+                ColumnDetail {
+                    header: "Name".to_string(),
+                    width: 240,
+                },
+                ColumnDetail {
+                    header: "Id".to_string(),
+                    width: 80,
+                },
+                ColumnDetail {
+                    header: "Value".to_string(),
+                    width: 100,
+                },
+            ],
+        }
+    }
+
+    fn get_cell(&mut self, node: &Vec<usize>, col: i32) -> SimpleCell {
+        let ecu = &self.ecus[node[0]];
+        match (node.len(), col) {
+            (1, 0) => SimpleCell::Text(ecu.name.to_string()),
+            (1, 1) => SimpleCell::Int(ecu.address as i64),
+            (2, 0) => SimpleCell::Text(self.pgn(node).name.to_string()),
+            (2, 1) => SimpleCell::Int(self.pgn(node).pgn as i64),
+            (3, _) => {
+                let spn = &self.pgn(node).spns.as_ref().unwrap()[node[2]];
+                match col {
+                    0 => SimpleCell::Text(spn.name.to_string()),
+                    1 => SimpleCell::Int(spn.spn as i64),
+                    _ => SimpleCell::Float {
+                        value: spn.value,
+                        precision: 1,
+                    },
+                }
+            }
+            _ => SimpleCell::None,
+        }
+    }
+}
+
+/// Pretend to request a PGN's SPNs from the bus.
+fn load_spns(tree: &mut J1939Tree, node: &[usize]) {
+    if node.len() != 2 {
+        return;
+    }
+    let pgn = &mut tree.ecus[node[0]].pgns[node[1]];
+    if pgn.spns.is_none() {
+        let base = pgn.pgn;
+        pgn.spns = Some(
+            ["Speed", "Temperature", "Pressure"]
+                .iter()
+                .enumerate()
+                .map(|(i, name)| Spn {
+                    spn: base + i as u32,
+                    name,
+                    value: (base % 97) as f64 + i as f64 * 10.5,
+                })
+                .collect(),
+        );
+    }
+}
+
+/// demonstration of a tree shown as a table.  Use + and - or Enter to expand and collapse rows.
+fn main() {
+    let pgns = |names: &[(u32, &'static str)]| {
+        names
+            .iter()
+            .map(|(pgn, name)| Pgn {
+                pgn: *pgn,
+                name,
+                spns: None,
+            })
+            .collect()
+    };
+    let tree = J1939Tree {
+        ecus: vec![
+            Ecu {
+                address: 0,
+                name: "Engine",
+                pgns: pgns(&[(61444, "EEC1"), (65262, "ET1")]),
+            },
+            Ecu {
+                address: 3,
+                name: "Transmission",
+                pgns: pgns(&[(61442, "ETC1"), (65272, "TRF1")]),
+            },
+        ],
+        expanded: HashSet::new(),
+    };
+
+    let app = app::App::default();
+    let mut wind = Window::default().with_size(440, 300).with_label("Tree");
+    let model = TreeTableModel::new(tree).with_loader(|tree, node| load_spns(tree, node));
+    let table = SimpleTable::new(fltk::table::Table::default_fill(), model);
+    wind.resizable(&table.table);
+    wind.end();
+    wind.show();
+    app.run().unwrap();
+}
@@ -0,0 +1,70 @@
+use fltk::{
+    app,
+    enums::Color,
+    prelude::*,
+    window::Window,
+};
+use simple_table::{
+    simple_model::{CellStyle, SimpleCell, VecColumn, VecModel},
+    simple_table::*,
+};
+
+/// Example BusinessObject representing a row
+struct Person {
+    id: u64,
+    name: String,
+    age: u32,
+}
+
+/// demonstration of a table built from column definitions, without implementing SimpleModel.
+fn main() {
+    let people = vec![
+        Person {
+            id: 1,
+            name: "Joe".to_string(),
+            age: 50,
+        },
+        Person {
+            id: 2,
+            name: "Bob".to_string(),
+            age: 35,
+        },
+        Person {
+            id: 3,
+            name: "Judy".to_string(),
+            age: 25,
+        },
+    ];
+    let mut model = VecModel::new(
+        people,
+        vec![
+            VecColumn::new("Name", 120, |p: &Person| SimpleCell::Text(p.name.clone()))
+                .with_hover(|p| Some(format!("The name is {}", p.name)))
+                .with_edit(|p, value| {
+                    p.name = value.to_string();
+                    Ok(())
+                }),
+            // typed cells sort as numbers and are right aligned
+            VecColumn::new("Age", 60, |p: &Person| SimpleCell::Int(p.age as i64)),
+        ],
+    )
+    // keep the selection on the same people when sorting
+    .with_row_key(|p| p.id)
+    // highlight the oldest
+    .with_row_style(|p| CellStyle {
+        fg: (p.age >= 50).then_some(Color::Red),
+        bold: p.age >= 50,
+        ..Default::default()
+    });
+    // hide the youngest
+    model.set_filter(|p| p.age > 30);
+
+    let app = app::App::default();
+    let mut wind = Window::default().with_size(200, 300).with_label("Counter");
+    let mut table = SimpleTable::new(fltk::table::Table::default_fill(), model);
+    table.set_row_header(true);
+    wind.resizable(&table.table);
+    wind.end();
+    wind.show();
+    app.run().unwrap();
+}
@@ -0,0 +1,63 @@
+use fltk::{
+    draw,
+    enums::{Align, Color, FrameType},
+    prelude::ImageExt,
+};
+
+use crate::simple_model::SimpleCell;
+use crate::theme::TableTheme;
+
+/// Largest checkbox drawn for a `SimpleCell::Bool`.
+const CHECK_SIZE: i32 = 14;
+
+/// Draw the value of a text or typed cell, shared by both tables.  Delegates and widgets are drawn by the table.
+/// `align` comes from the cell style, and overrides the cell's own alignment.
+pub(crate) fn draw_value(
+    cell: &SimpleCell,
+    (x, y, w, h): (i32, i32, i32, i32),
+    align: Option<Align>,
+    color: Color,
+    theme: &TableTheme,
+) {
+    let mut align = align.unwrap_or(cell.align());
+    if let SimpleCell::AlignedText { wrap: true, .. } = cell {
+        align |= Align::Wrap;
+    }
+    match cell {
+        SimpleCell::Bool(checked) => {
+            let size = CHECK_SIZE.min(w).min(h);
+            let bx = x + (w - size) / 2;
+            let by = y + (h - size) / 2;
+            draw::draw_box(FrameType::DownBox, bx, by, size, size, theme.background);
+            if *checked {
+                draw::draw_check(bx + 2, by + 2, size - 4, size - 4, theme.foreground);
+            }
+        }
+        SimpleCell::Progress(fraction) => {
+            let filled = (w as f32 * fraction.clamp(0.0, 1.0)) as i32;
+            draw::draw_rect_fill(x, y + 1, filled, h - 2, theme.series_color(1));
+            draw::set_draw_color(theme.grid);
+            draw::draw_rect(x, y + 1, w, h - 2);
+            draw::set_draw_color(color);
+            draw::draw_text2(&cell.to_text().unwrap_or_default(), x, y, w, h, align);
+        }
+        SimpleCell::Image(image) => {
+            let mut image = image.clone();
+            let ix = if align.contains(Align::Left) {
+                x
+            } else if align.contains(Align::Right) {
+                x + w - image.w()
+            } else {
+                x + (w - image.w()) / 2
+            };
+            let iy = y + (h - image.h()) / 2;
+            image.draw(ix, iy, image.w(), image.h());
+        }
+        _ => {
+            if let Some(text) = cell.to_text() {
+                draw::set_draw_color(color);
+                draw::draw_text2(&text, x, y, w, h, align);
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::sync::{Arc, Mutex};
+
+use fltk::{
+    app,
+    button::CheckButton,
+    enums::{Align, Color, Event, FrameType, Key},
+    frame::Frame,
+    group::Group,
+    input::Input,
+    menu::Choice,
+    misc::Spinner,
+    prelude::{GroupExt, InputExt, MenuExt, WidgetBase, WidgetExt},
+};
+
+use crate::simple_model::{EditorKind, SimpleModel};
+
+/// Shared by a table widget and its open editor, so that either can close it.
+pub(crate) type EditorSlot = Arc<Mutex<Option<CellEditor>>>;
+
+const ERROR_WIDTH: i32 = 200;
+
+/// Overlay editor for a single cell.
+/// Enter commits the value with `SimpleModel::set_cell`, Escape cancels.
+/// A rejected value leaves the editor open, with the error shown next to the cell.
+pub struct CellEditor {
+    group: Group,
+}
+
+enum EditorWidget {
+    Text(Input),
+    Integer(Spinner),
+    Check(CheckButton),
+    Choice(Choice),
+}
+
+impl EditorWidget {
+    fn new(kind: EditorKind, current: &str, x: i32, y: i32, w: i32, h: i32) -> EditorWidget {
+        match kind {
+            EditorKind::Text => {
+                let mut input = Input::new(x, y, w, h, None);
+                input.set_value(current);
+                EditorWidget::Text(input)
+            }
+            EditorKind::Integer { min, max } => {
+                let mut spinner = Spinner::new(x, y, w, h, None);
+                spinner.set_range(min as f64, max as f64);
+                spinner.set_step(1.0);
+                spinner.set_value(current.trim().parse::<i64>().unwrap_or(min) as f64);
+                EditorWidget::Integer(spinner)
+            }
+            EditorKind::Check => {
+                let mut check = CheckButton::new(x, y, w, h, None);
+                check.set_color(Color::White);
+                check.set_frame(FrameType::FlatBox);
+                check.set_checked(current.trim().eq_ignore_ascii_case("true"));
+                EditorWidget::Check(check)
+            }
+            EditorKind::Choice(choices) => {
+                let mut choice = Choice::new(x, y, w, h, None);
+                for c in &choices {
+                    choice.add_choice(c);
+                }
+                if let Some(index) = choices.iter().position(|c| c == current) {
+                    choice.set_value(index as i32);
+                }
+                EditorWidget::Choice(choice)
+            }
+        }
+    }
+
+    fn value(&self) -> String {
+        match self {
+            EditorWidget::Text(input) => input.value(),
+            EditorWidget::Integer(spinner) => (spinner.value() as i64).to_string(),
+            EditorWidget::Check(check) => check.is_checked().to_string(),
+            EditorWidget::Choice(choice) => choice.choice().unwrap_or_default(),
+        }
+    }
+
+    fn take_focus(&mut self) {
+        let _ = match self {
+            EditorWidget::Text(w) => w.take_focus(),
+            EditorWidget::Integer(w) => w.take_focus(),
+            EditorWidget::Check(w) => w.take_focus(),
+            EditorWidget::Choice(w) => w.take_focus(),
+        };
+    }
+}
+
+impl CellEditor {
+    /// Open an editor over the cell at (x, y, w, h), replacing any editor already open in the slot.
+    /// `on_close` is called after the editor is removed, so the table can redraw.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn open<T, P>(
+        slot: &EditorSlot,
+        parent: &mut P,
+        model: Arc<Mutex<T>>,
+        row: i32,
+        col: i32,
+        (x, y, w, h): (i32, i32, i32, i32),
+        on_close: impl FnMut() + 'static,
+    ) where
+        T: SimpleModel + ?Sized + 'static,
+        P: GroupExt,
+    {
+        Self::close(slot);
+        let (kind, current) = {
+            let mut model = model.lock().unwrap();
+            if !model.is_editable(row, col) {
+                return;
+            }
+            let current = model.get_cell(row, col).to_text().unwrap_or_default();
+            (model.editor(row, col), current)
+        };
+
+        let mut group = Group::new(x, y, w + ERROR_WIDTH, h, None);
+        let editor = EditorWidget::new(kind, &current, x, y, w, h);
+        let mut error = Frame::new(x + w, y, ERROR_WIDTH, h, None);
+        error.set_frame(FrameType::FlatBox);
+        error.set_color(Color::from_u32(0x00FF_FFE0));
+        error.set_label_color(Color::Red);
+        error.set_align(Align::Left | Align::Inside);
+        error.hide();
+        group.end();
+        parent.add(&group);
+
+        let on_close = Arc::new(Mutex::new(on_close));
+        {
+            let slot = slot.clone();
+            let editor = Arc::new(Mutex::new(editor));
+            let focus = editor.clone();
+            group.handle(move |g, e| {
+                if e != Event::KeyDown {
+                    return false;
+                }
+                match app::event_key() {
+                    Key::Enter | Key::KPEnter => {
+                        let value = editor.lock().unwrap().value();
+                        let result = model.lock().unwrap().set_cell(row, col, &value);
+                        match result {
+                            Ok(_) => {
+                                Self::close(&slot);
+                                (on_close.lock().unwrap())();
+                            }
+                            Err(msg) => {
+                                error.set_label(&msg);
+                                error.show();
+                                g.redraw();
+                            }
+                        }
+                        true
+                    }
+                    Key::Escape => {
+                        Self::close(&slot);
+                        (on_close.lock().unwrap())();
+                        true
+                    }
+                    _ => false,
+                }
+            });
+            focus.lock().unwrap().take_focus();
+        }
+        slot.lock().unwrap().replace(CellEditor { group });
+    }
+
+    /// Close the open editor, discarding its value.
+    pub(crate) fn close(slot: &EditorSlot) {
+        let editor = slot.lock().unwrap().take();
+        if let Some(editor) = editor {
+            let mut group = editor.group;
+            if let Some(mut parent) = group.parent() {
+                parent.remove(&group);
+                parent.redraw();
+            }
+            group.hide();
+            app::delete_widget(group);
+        }
+    }
+
+    /// True if the current event is over the open editor, which should handle it.
+    pub(crate) fn event_inside(slot: &EditorSlot) -> bool {
+        match slot.lock().unwrap().as_ref() {
+            Some(editor) => app::event_inside_widget(&editor.group),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
+use fltk::draw;
+
+use crate::simple_model::{ColumnDetail, SimpleModel};
+
+/// Narrowest a column can be resized to.
+pub const MIN_WIDTH: u32 = 10;
+/// Space around measured text when fitting a column.
+const FIT_PADDING: i32 = 8;
+/// Space for the sort indicator when fitting a header.
+const SORT_INDICATOR_WIDTH: i32 = 16;
+
+/// Per table view of the model's columns.  Maps displayed columns to model columns,
+/// and keeps the widths and hidden columns users have set, so users can rearrange columns without
+/// changing the model.
+#[derive(Debug, Clone, Default)]
+pub struct ColumnView {
+    /// every model column, in display order
+    order: Vec<usize>,
+    /// widths set by the user, by model column
+    widths: HashMap<usize, u32>,
+    /// model columns hidden by the user
+    hidden: HashSet<usize>,
+    /// model column for each displayed column
+    displayed: Vec<usize>,
+}
+
+impl ColumnView {
+    /// Keep up with the model's column count.  New columns are displayed last.
+    pub fn update(&mut self, count: usize) {
+        if self.order.len() != count {
+            self.order.retain(|c| *c < count);
+            for c in 0..count {
+                if !self.order.contains(&c) {
+                    self.order.push(c);
+                }
+            }
+            self.hidden.retain(|c| *c < count);
+            self.refresh();
+        }
+    }
+
+    fn refresh(&mut self) {
+        self.displayed = self
+            .order
+            .iter()
+            .copied()
+            .filter(|c| !self.hidden.contains(c))
+            .collect();
+    }
+
+    /// Every model column in display order, including hidden columns.
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    /// Model columns that are displayed, in display order.
+    pub fn displayed(&self) -> &[usize] {
+        &self.displayed
+    }
+
+    /// Set the display order.  Columns missing from the order are displayed last, unknown columns are ignored.
+    pub fn set_order(&mut self, order: Vec<usize>) {
+        let count = self.order.len();
+        let mut new_order: Vec<usize> = Vec::with_capacity(count);
+        for c in order {
+            if c < count && !new_order.contains(&c) {
+                new_order.push(c);
+            }
+        }
+        self.order = new_order;
+        self.update(count);
+        self.refresh();
+    }
+
+    /// Model column for a displayed column, or None past the last displayed column.
+    pub fn model_col(&self, display_col: usize) -> Option<usize> {
+        self.displayed.get(display_col).copied()
+    }
+
+    /// Displayed column for a model column, or None if it is hidden.
+    pub fn display_col(&self, model_col: usize) -> Option<usize> {
+        self.displayed.iter().position(|c| *c == model_col)
+    }
+
+    /// Move a displayed column to another display position.
+    pub fn move_col(&mut self, from: usize, to: usize) {
+        if from < self.displayed.len() && to < self.displayed.len() && from != to {
+            let col = self.displayed[from];
+            let target = self.displayed[to];
+            self.order.retain(|c| *c != col);
+            let index = self.order.iter().position(|c| *c == target).unwrap();
+            let index = if from < to { index + 1 } else { index };
+            self.order.insert(index, col);
+            self.refresh();
+        }
+    }
+
+    /// Show every column in model order, at the model's widths.
+    pub fn reset(&mut self) {
+        self.order.sort_unstable();
+        self.widths.clear();
+        self.hidden.clear();
+        self.refresh();
+    }
+
+    /// Width of a model column, or the default if the user hasn't set one.
+    pub fn width(&self, model_col: usize, default: u32) -> u32 {
+        self.widths.get(&model_col).copied().unwrap_or(default)
+    }
+
+    /// Override the model's width for a column.
+    pub fn set_width(&mut self, model_col: usize, width: u32) {
+        self.widths.insert(model_col, width.max(MIN_WIDTH));
+    }
+
+    /// Widths set by the user, by model column.
+    pub fn widths(&self) -> &HashMap<usize, u32> {
+        &self.widths
+    }
+
+    /// Go back to the model's widths.
+    pub fn clear_widths(&mut self) {
+        self.widths.clear();
+    }
+
+    pub fn is_hidden(&self, model_col: usize) -> bool {
+        self.hidden.contains(&model_col)
+    }
+
+    /// Hide or show a model column.  The last displayed column can't be hidden.
+    pub fn set_hidden(&mut self, model_col: usize, hidden: bool) {
+        if hidden {
+            if self.displayed.iter().any(|c| *c != model_col) {
+                self.hidden.insert(model_col);
+            }
+        } else {
+            self.hidden.remove(&model_col);
+        }
+        self.refresh();
+    }
+
+    /// Width of all displayed columns.
+    pub fn total_width(&self, details: &[ColumnDetail]) -> u32 {
+        self.displayed
+            .iter()
+            .map(|c| self.width(*c, details[*c].width))
+            .sum()
+    }
+
+    /// True if every column is displayed, in model order.
+    pub fn is_identity(&self) -> bool {
+        self.hidden.is_empty() && self.order.iter().enumerate().all(|(i, c)| i == *c)
+    }
+
+    /// Paste tab separated values starting at a displayed column.  When columns are reordered or hidden,
+    /// each value is pasted separately so that it lands in the displayed column, and values past the last
+    /// displayed column are dropped.
+    pub fn paste<T: SimpleModel + ?Sized>(
+        &self,
+        model: &mut T,
+        row: i32,
+        display_col: usize,
+        text: &str,
+    ) -> Result<(), String> {
+        if self.is_identity() {
+            return model.paste(row, display_col as i32, text);
+        }
+        text.lines().enumerate().try_for_each(|(r, line)| {
+            line.split('\t')
+                .enumerate()
+                .map_while(|(c, value)| Some((self.model_col(display_col + c)?, value)))
+                .try_for_each(|(col, value)| model.paste(row + r as i32, col as i32, value))
+        })
+    }
+}
+
+/// Width that fits the header and the text of the cells in rows, measured with the current font.
+pub(crate) fn fit_width<T: SimpleModel + ?Sized>(
+    model: &mut T,
+    col: usize,
+    rows: Range<usize>,
+) -> u32 {
+    let header = match model.column_info().details.get(col) {
+        Some(detail) => detail.header.clone(),
+        None => return MIN_WIDTH,
+    };
+    let mut width = draw::measure(&header, false).0 + SORT_INDICATOR_WIDTH;
+    for row in rows {
+        if let Some(text) = model.get_cell(row as i32, col as i32).to_text() {
+            let text_width = text.lines().map(|l| draw::measure(l, false).0).max();
+            width = width.max(text_width.unwrap_or_default());
+        }
+    }
+    ((width + FIT_PADDING) as u32).max(MIN_WIDTH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_model::{ColumnInfo, RowHeight, RowInfo, SimpleCell};
+
+    fn view(count: usize) -> ColumnView {
+        let mut view = ColumnView::default();
+        view.update(count);
+        view
+    }
+
+    /// Records every value passed to `set_cell`.
+    struct Recorder {
+        cols: usize,
+        set: Vec<(i32, i32, String)>,
+    }
+    impl SimpleModel for Recorder {
+        fn row_info(&mut self) -> RowInfo {
+            RowInfo {
+                count: 10,
+                height: RowHeight::All(20),
+            }
+        }
+        fn column_info(&mut self) -> ColumnInfo {
+            ColumnInfo {
+                details: (0..self.cols)
+                    .map(|c| ColumnDetail {
+                        header: c.to_string(),
+                        width: 50,
+                    })
+                    .collect(),
+            }
+        }
+        fn get_cell(&mut self, _row: i32, _col: i32) -> SimpleCell {
+            SimpleCell::None
+        }
+        fn is_editable(&self, _row: i32, _col: i32) -> bool {
+            true
+        }
+        fn set_cell(&mut self, row: i32, col: i32, value: &str) -> Result<(), String> {
+            self.set.push((row, col, value.to_string()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn move_and_hide() {
+        let mut view = view(4);
+        assert!(view.is_identity());
+        view.move_col(0, 2);
+        assert_eq!(view.displayed(), &[1, 2, 0, 3]);
+        view.move_col(3, 0);
+        assert_eq!(view.displayed(), &[3, 1, 2, 0]);
+        view.set_hidden(1, true);
+        assert_eq!(view.displayed(), &[3, 2, 0]);
+        assert_eq!(view.order(), &[3, 1, 2, 0]);
+        assert_eq!(view.model_col(1), Some(2));
+        assert_eq!(view.model_col(3), None);
+        assert_eq!(view.display_col(0), Some(2));
+        assert_eq!(view.display_col(1), None);
+        view.reset();
+        assert!(view.is_identity());
+    }
+
+    #[test]
+    fn last_column_stays_displayed() {
+        let mut view = view(2);
+        view.set_hidden(0, true);
+        view.set_hidden(1, true);
+        assert_eq!(view.displayed(), &[1]);
+    }
+
+    #[test]
+    fn order_follows_column_count() {
+        let mut view = view(3);
+        view.set_order(vec![2, 9, 2, 0]);
+        assert_eq!(view.order(), &[2, 0, 1]);
+        view.set_hidden(2, true);
+        view.update(2);
+        assert_eq!(view.order(), &[0, 1]);
+        assert!(!view.is_hidden(2));
+        view.update(3);
+        assert_eq!(view.displayed(), &[0, 1, 2]);
+    }
+
+    #[test]
+    fn widths() {
+        let mut view = view(2);
+        assert_eq!(view.width(1, 80), 80);
+        view.set_width(1, 2);
+        assert_eq!(view.width(1, 80), MIN_WIDTH);
+        let details: Vec<ColumnDetail> = [30, 40]
+            .iter()
+            .map(|w| ColumnDetail {
+                header: String::new(),
+                width: *w,
+            })
+            .collect();
+        assert_eq!(view.total_width(&details), 30 + MIN_WIDTH);
+        view.set_hidden(0, true);
+        assert_eq!(view.total_width(&details), MIN_WIDTH);
+        view.clear_widths();
+        assert!(view.widths().is_empty());
+    }
+
+    #[test]
+    fn paste_into_displayed_columns() {
+        let mut view = view(4);
+        view.move_col(3, 0);
+        view.set_hidden(1, true);
+        // displayed: 3, 0, 2
+        let mut model = Recorder {
+            cols: 4,
+            set: Vec::new(),
+        };
+        view.paste(&mut model, 1, 1, "a\tb\tc\nd").unwrap();
+        let expected = vec![
+            (1, 0, "a".to_string()),
+            (1, 2, "b".to_string()),
+            (2, 0, "d".to_string()),
+        ];
+        assert_eq!(model.set, expected);
+    }
+}
@@ -0,0 +1,44 @@
+use fltk::enums::Key;
+
+/// Cell with the keyboard focus, by row and displayed column.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct CellCursor {
+    pub row: i32,
+    pub col: i32,
+}
+
+impl CellCursor {
+    pub fn set(&mut self, row: i32, col: i32) {
+        self.row = row;
+        self.col = col;
+    }
+
+    /// Move for an arrow, page, Home or End key.  Returns false for other keys, or an empty table.
+    pub fn navigate(&mut self, key: Key, rows: i32, cols: i32, page: i32) -> bool {
+        if rows <= 0 || cols <= 0 {
+            return false;
+        }
+        let (row, col) = match key {
+            Key::Up => (self.row - 1, self.col),
+            Key::Down => (self.row + 1, self.col),
+            Key::Left => (self.row, self.col - 1),
+            Key::Right => (self.row, self.col + 1),
+            Key::PageUp => (self.row - page.max(1), self.col),
+            Key::PageDown => (self.row + page.max(1), self.col),
+            Key::Home => (0, self.col),
+            Key::End => (rows - 1, self.col),
+            _ => return false,
+        };
+        self.set(row.clamp(0, rows - 1), col.clamp(0, cols - 1));
+        true
+    }
+
+    pub fn is_at(&self, row: i32, col: i32) -> bool {
+        self.row == row && self.col == col
+    }
+}
+
+/// True for the keys that fire a cell's action.
+pub(crate) fn is_enter(key: Key) -> bool {
+    key == Key::Enter || key == Key::KPEnter
+}
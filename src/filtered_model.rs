@@ -0,0 +1,384 @@
+use std::{
+    collections::HashMap,
+    mem,
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+use crate::model_events::{ModelEvent, ModelEvents};
+use crate::simple_model::{
+    CellStyle, ColumnInfo, EditorKind, Order, RowHeight, RowInfo, SimpleCell, SimpleModel,
+};
+
+type CellPredicate = Box<dyn Fn(&SimpleCell) -> bool + Send>;
+
+/// Filters the rows of another model, by a text match over all cells and by per-column predicates.
+/// Visible rows are mapped to source rows through an index.  Rows appended to the source model are
+/// filtered as they arrive, so tables refreshed with `redraw_on` keep working as the source grows.
+pub struct FilteredModel<M: SimpleModel> {
+    pub model: M,
+    text: String,
+    predicates: HashMap<usize, CellPredicate>,
+    /// source rows, in display order
+    index: Arc<Vec<usize>>,
+    /// number of source rows already filtered
+    checked: usize,
+    /// source model changes not yet applied to the index
+    pending: Arc<Mutex<Vec<ModelEvent>>>,
+    events: Option<ModelEvents>,
+}
+
+impl<M: SimpleModel> FilteredModel<M> {
+    pub fn new(mut model: M) -> FilteredModel<M> {
+        let pending: Arc<Mutex<Vec<ModelEvent>>> = Default::default();
+        let events = model.events().map(|source| {
+            let events = ModelEvents::new();
+            let forward = events.clone();
+            let pending = pending.clone();
+            source.subscribe(move |event| {
+                pending.lock().unwrap().push(event.clone());
+                // displayed rows are only known after filtering
+                forward.reset();
+            });
+            events
+        });
+        FilteredModel {
+            model,
+            text: String::new(),
+            predicates: HashMap::new(),
+            index: Default::default(),
+            checked: 0,
+            pending,
+            events,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Only show rows with a cell containing the text, ignoring case.
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_lowercase();
+        self.refilter();
+    }
+
+    /// Only show rows where the column's cell matches the predicate.
+    pub fn set_column_filter(
+        &mut self,
+        col: usize,
+        predicate: impl Fn(&SimpleCell) -> bool + Send + 'static,
+    ) {
+        self.predicates.insert(col, Box::new(predicate));
+        self.refilter();
+    }
+
+    pub fn clear_column_filter(&mut self, col: usize) {
+        self.predicates.remove(&col);
+        self.refilter();
+    }
+
+    /// Source model row for a displayed row.
+    pub fn source_row(&self, row: usize) -> Option<usize> {
+        self.index.get(row).copied()
+    }
+
+    /// Rebuild the index on the next `row_info`.
+    pub fn refilter(&mut self) {
+        Arc::make_mut(&mut self.index).clear();
+        self.checked = 0;
+    }
+
+    fn accept(&mut self, row: usize, cols: usize) -> bool {
+        for (col, predicate) in &self.predicates {
+            if !predicate(&self.model.get_cell(row as i32, *col as i32)) {
+                return false;
+            }
+        }
+        self.text.is_empty()
+            || (0..cols).any(|col| {
+                self.model
+                    .get_cell(row as i32, col as i32)
+                    .to_text()
+                    .is_some_and(|s| s.to_lowercase().contains(&self.text))
+            })
+    }
+
+    /// Apply the source model's changes.  Updated rows are filtered again, and anything but appended rows
+    /// rebuilds the index.
+    fn apply_events(&mut self) {
+        let events = mem::take(&mut *self.pending.lock().unwrap());
+        for event in events {
+            match event {
+                // appended rows are filtered as they arrive
+                ModelEvent::RowsInserted(rows) if rows.start >= self.checked => {}
+                ModelEvent::RowsUpdated(rows) => self.recheck(rows),
+                _ => self.refilter(),
+            }
+        }
+    }
+
+    fn recheck(&mut self, rows: Range<usize>) {
+        let cols = self.model.column_info().details.len();
+        for row in rows.start..rows.end.min(self.checked) {
+            let accepted = self.accept(row, cols);
+            // the index is in source order
+            let index = Arc::make_mut(&mut self.index);
+            match (index.binary_search(&row), accepted) {
+                (Err(i), true) => index.insert(i, row),
+                (Ok(i), false) => {
+                    index.remove(i);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn update_index(&mut self, count: usize) {
+        if count < self.checked {
+            // rows were removed
+            self.refilter();
+        }
+        if count > self.checked {
+            let cols = self.model.column_info().details.len();
+            let new_rows: Vec<usize> = (self.checked..count)
+                .filter(|row| self.accept(*row, cols))
+                .collect();
+            Arc::make_mut(&mut self.index).extend(new_rows);
+            self.checked = count;
+        }
+    }
+
+    fn source(&self, row: i32) -> Option<i32> {
+        self.source_row(row as usize).map(|r| r as i32)
+    }
+}
+
+impl<M: SimpleModel> SimpleModel for FilteredModel<M> {
+    fn row_info(&mut self) -> RowInfo {
+        let row_info = self.model.row_info();
+        self.apply_events();
+        self.update_index(row_info.count);
+        let height = match row_info.height {
+            RowHeight::All(h) => RowHeight::All(h),
+            RowHeight::PerRow(f) => {
+                let index = self.index.clone();
+                RowHeight::PerRow(Box::new(move |row| {
+                    index.get(row).map(|r| f(*r)).unwrap_or_default()
+                }))
+            }
+        };
+        RowInfo {
+            count: self.index.len(),
+            height,
+        }
+    }
+
+    fn column_info(&mut self) -> ColumnInfo {
+        self.model.column_info()
+    }
+
+    /// Source model changes, reported as `Reset` since the displayed rows are only known after filtering.
+    fn events(&mut self) -> Option<ModelEvents> {
+        self.events.clone()
+    }
+
+    fn get_cell(&mut self, row: i32, col: i32) -> SimpleCell {
+        match self.source(row) {
+            Some(row) => self.model.get_cell(row, col),
+            None => SimpleCell::None,
+        }
+    }
+
+    fn hover(&self, row: i32, col: i32) -> Option<String> {
+        self.model.hover(self.source(row)?, col)
+    }
+
+    fn expand(&mut self, row: i32, expand: bool) -> bool {
+        let expanded = self
+            .source(row)
+            .is_some_and(|row| self.model.expand(row, expand));
+        if expanded {
+            self.refilter();
+        }
+        expanded
+    }
+
+    fn click(&mut self, row: i32, col: i32) -> bool {
+        let clicked = self
+            .source(row)
+            .is_some_and(|row| self.model.click(row, col));
+        if clicked {
+            self.refilter();
+        }
+        clicked
+    }
+
+    fn row_header(&mut self, row: i32) -> Option<String> {
+        self.model.row_header(self.source(row)?)
+    }
+
+    fn row_key(&mut self, row: i32) -> Option<u64> {
+        self.model.row_key(self.source(row)?)
+    }
+
+    fn row_style(&mut self, row: i32) -> CellStyle {
+        match self.source(row) {
+            Some(row) => self.model.row_style(row),
+            None => CellStyle::default(),
+        }
+    }
+
+    fn cell_style(&mut self, row: i32, col: i32) -> CellStyle {
+        match self.source(row) {
+            Some(row) => self.model.cell_style(row, col),
+            None => CellStyle::default(),
+        }
+    }
+
+    fn sort(&mut self, col: usize, order: Order) {
+        self.model.sort(col, order);
+        self.refilter();
+    }
+
+    fn sort_multi(&mut self, keys: &[(usize, Order)]) {
+        self.model.sort_multi(keys);
+        self.refilter();
+    }
+
+    fn is_editable(&self, row: i32, col: i32) -> bool {
+        self.source(row)
+            .is_some_and(|row| self.model.is_editable(row, col))
+    }
+
+    fn editor(&self, row: i32, col: i32) -> EditorKind {
+        match self.source(row) {
+            Some(row) => self.model.editor(row, col),
+            None => EditorKind::Text,
+        }
+    }
+
+    fn set_cell(&mut self, row: i32, col: i32, value: &str) -> Result<(), String> {
+        let row = self.source(row).ok_or("No such row")?;
+        self.model.set_cell(row, col, value)
+    }
+
+    fn activate(&mut self, row: i32, col: i32) -> bool {
+        self.source(row)
+            .is_some_and(|row| self.model.activate(row, col))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_model::ColumnDetail;
+
+    struct Source {
+        rows: Vec<(String, i64)>,
+        events: ModelEvents,
+    }
+    impl SimpleModel for Source {
+        fn row_info(&mut self) -> RowInfo {
+            RowInfo {
+                count: self.rows.len(),
+                height: RowHeight::PerRow(Box::new(|r| 10 + r as u32)),
+            }
+        }
+        fn column_info(&mut self) -> ColumnInfo {
+            ColumnInfo {
+                details: ["Name", "Count"]
+                    .iter()
+                    .map(|h| ColumnDetail {
+                        header: h.to_string(),
+                        width: 50,
+                    })
+                    .collect(),
+            }
+        }
+        fn get_cell(&mut self, row: i32, col: i32) -> SimpleCell {
+            let (name, count) = &self.rows[row as usize];
+            match col {
+                0 => SimpleCell::Text(name.clone()),
+                _ => SimpleCell::Int(*count),
+            }
+        }
+        fn events(&mut self) -> Option<ModelEvents> {
+            Some(self.events.clone())
+        }
+    }
+
+    fn filtered(rows: &[(&str, i64)]) -> (FilteredModel<Source>, ModelEvents) {
+        let events = ModelEvents::new();
+        let source = Source {
+            rows: rows.iter().map(|(n, c)| (n.to_string(), *c)).collect(),
+            events: events.clone(),
+        };
+        (FilteredModel::new(source), events)
+    }
+
+    fn shown(model: &mut FilteredModel<Source>) -> Vec<usize> {
+        let count = model.row_info().count;
+        (0..count).filter_map(|r| model.source_row(r)).collect()
+    }
+
+    #[test]
+    fn text_and_column_filters() {
+        let (mut model, _) = filtered(&[("Apple", 1), ("banana", 2), ("apricot", 3)]);
+        assert_eq!(shown(&mut model), vec![0, 1, 2]);
+        model.set_text("AP");
+        assert_eq!(shown(&mut model), vec![0, 2]);
+        model.set_column_filter(1, |c| c.number().is_some_and(|n| n > 1.0));
+        assert_eq!(shown(&mut model), vec![2]);
+        assert_eq!(model.get_cell(0, 0).as_str(), Some("apricot"));
+        assert!(model.get_cell(1, 0).as_str().is_none());
+        model.clear_column_filter(1);
+        model.set_text("");
+        assert_eq!(shown(&mut model), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn row_heights_follow_index() {
+        let (mut model, _) = filtered(&[("a", 0), ("b", 0), ("ab", 0)]);
+        model.set_text("a");
+        let info = model.row_info();
+        assert_eq!(info.height.for_row(1), 12);
+        assert_eq!(info.height.for_row(5), 0);
+    }
+
+    #[test]
+    fn appended_rows_are_filtered() {
+        let (mut model, events) = filtered(&[("a", 0), ("b", 0)]);
+        model.set_text("a");
+        assert_eq!(shown(&mut model), vec![0]);
+        model.model.rows.push(("ca".to_string(), 0));
+        model.model.rows.push(("c".to_string(), 0));
+        events.rows_inserted(2..4);
+        assert_eq!(shown(&mut model), vec![0, 2]);
+        assert_eq!(model.checked, 4);
+    }
+
+    #[test]
+    fn updated_rows_are_rechecked() {
+        let (mut model, events) = filtered(&[("a", 0), ("b", 0), ("a", 0)]);
+        model.set_text("a");
+        assert_eq!(shown(&mut model), vec![0, 2]);
+        model.model.rows[1].0 = "ab".to_string();
+        model.model.rows[2].0 = "c".to_string();
+        events.rows_updated(1..3);
+        assert_eq!(shown(&mut model), vec![0, 1]);
+    }
+
+    #[test]
+    fn removed_rows_rebuild_index() {
+        let (mut model, events) = filtered(&[("a", 0), ("b", 0), ("a", 0)]);
+        model.set_text("a");
+        assert_eq!(shown(&mut model), vec![0, 2]);
+        model.model.rows.remove(0);
+        events.rows_removed(0..1);
+        assert_eq!(shown(&mut model), vec![1]);
+        // removing without an event is noticed from the row count
+        model.model.rows.pop();
+        assert_eq!(shown(&mut model), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,616 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use crate::model_events::ModelEvents;
+use crate::simple_model::{
+    CellStyle, ColumnInfo, EditorKind, Order, RowHeight, RowInfo, SimpleCell, SimpleModel,
+};
+use crate::theme::TableTheme;
+use crate::tree_model::TreeCell;
+
+/// Summary of a column's numeric cells, shown in group header rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Min,
+    Max,
+    Avg,
+}
+
+impl Aggregate {
+    /// Combine the numeric cells, or None if there are none.  Integers are added without rounding.
+    fn apply(&self, cells: impl Iterator<Item = SimpleCell>) -> Option<Total> {
+        let mut ints = Vec::new();
+        let mut floats = Vec::new();
+        for cell in cells {
+            match cell {
+                SimpleCell::Int(i) => ints.push(i),
+                cell => floats.extend(cell.number()),
+            }
+        }
+        let count = ints.len() + floats.len();
+        if count == 0 {
+            return None;
+        }
+        if floats.is_empty() {
+            let sum: i128 = ints.iter().map(|i| *i as i128).sum();
+            return Some(match self {
+                // saturate rather than wrap
+                Aggregate::Sum => Total::Int(sum.clamp(i64::MIN as i128, i64::MAX as i128) as i64),
+                Aggregate::Min => Total::Int(*ints.iter().min()?),
+                Aggregate::Max => Total::Int(*ints.iter().max()?),
+                Aggregate::Avg => Total::Float(sum as f64 / count as f64),
+            });
+        }
+        floats.extend(ints.iter().map(|i| *i as f64));
+        Some(Total::Float(match self {
+            Aggregate::Sum => floats.iter().sum(),
+            Aggregate::Min => floats.iter().copied().fold(f64::INFINITY, f64::min),
+            Aggregate::Max => floats.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            Aggregate::Avg => floats.iter().sum::<f64>() / count as f64,
+        }))
+    }
+}
+
+/// Value of an aggregate, kept as an integer when every cell was one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Total {
+    Int(i64),
+    Float(f64),
+}
+
+/// Header row of a group.
+struct Group {
+    /// values of the grouped columns, outermost first
+    path: Vec<String>,
+    count: usize,
+    /// source row, for the header's height
+    first: usize,
+    /// (column, value) for each aggregated column
+    aggregates: Vec<(usize, Total)>,
+    collapsed: bool,
+}
+
+enum GroupRow {
+    Header(Group),
+    Row(usize),
+}
+
+/// Groups the rows of another model by the values of one or more columns.  Each group starts with a
+/// header row showing "value (count)" in the first column, and any aggregates in their columns.
+/// Clicking a header, or pressing Enter, + or - on it, collapses or expands the group.
+/// Groups are in the order their first rows appear, so sorting by a grouped column sorts the groups.
+pub struct GroupedModel<M: SimpleModel> {
+    pub model: M,
+    group_by: Vec<usize>,
+    aggregates: Vec<(usize, Aggregate)>,
+    /// paths of collapsed groups
+    collapsed: HashSet<Vec<String>>,
+    /// rows in display order
+    rows: Arc<Vec<GroupRow>>,
+    /// number of source rows when grouped
+    source_count: usize,
+    dirty: bool,
+    /// set when the source model reports a change
+    stale: Arc<AtomicBool>,
+    theme: TableTheme,
+    events: ModelEvents,
+}
+
+impl<M: SimpleModel> GroupedModel<M> {
+    pub fn new(mut model: M) -> GroupedModel<M> {
+        let stale: Arc<AtomicBool> = Default::default();
+        let events = ModelEvents::new();
+        if let Some(source) = model.events() {
+            let forward = events.clone();
+            let stale = stale.clone();
+            source.subscribe(move |_event| {
+                stale.store(true, Ordering::Relaxed);
+                // displayed rows are only known after grouping
+                forward.reset();
+            });
+        }
+        GroupedModel {
+            model,
+            group_by: Vec::new(),
+            aggregates: Vec::new(),
+            collapsed: HashSet::new(),
+            rows: Default::default(),
+            source_count: 0,
+            dirty: true,
+            stale,
+            theme: Default::default(),
+            events,
+        }
+    }
+
+    /// Draw the group headers with the colors and font of a theme, to match the table.
+    pub fn with_theme(mut self, theme: &TableTheme) -> Self {
+        self.theme = *theme;
+        self
+    }
+
+    /// Group by the values of columns, outermost first.  No columns shows the rows ungrouped.
+    pub fn set_group_by(&mut self, cols: &[usize]) {
+        self.group_by = cols.to_vec();
+        self.collapsed.clear();
+        self.regroup();
+    }
+
+    pub fn group_by(&self) -> &[usize] {
+        &self.group_by
+    }
+
+    /// Show a summary of a column in the group headers.
+    pub fn set_aggregate(&mut self, col: usize, aggregate: Aggregate) {
+        self.aggregates.retain(|(c, _)| *c != col);
+        self.aggregates.push((col, aggregate));
+        self.regroup();
+    }
+
+    pub fn clear_aggregate(&mut self, col: usize) {
+        self.aggregates.retain(|(c, _)| *c != col);
+        self.regroup();
+    }
+
+    /// Group again on the next `row_info`.
+    pub fn regroup(&mut self) {
+        self.dirty = true;
+        self.events.reset();
+    }
+
+    /// Source row shown in a row, or None for a group header.
+    pub fn source_row(&self, row: usize) -> Option<usize> {
+        match self.rows.get(row)? {
+            GroupRow::Row(r) => Some(*r),
+            GroupRow::Header(_) => None,
+        }
+    }
+
+    pub fn is_group(&self, row: usize) -> bool {
+        matches!(self.rows.get(row), Some(GroupRow::Header(_)))
+    }
+
+    /// Collapse or expand the group headed by a row.  Returns false if the row isn't a group header,
+    /// or is already collapsed or expanded.
+    pub fn set_collapsed(&mut self, row: usize, collapse: bool) -> bool {
+        let count = self.model.row_info().count;
+        self.update(count);
+        let path = match self.rows.get(row) {
+            Some(GroupRow::Header(group)) if group.collapsed != collapse => group.path.clone(),
+            _ => return false,
+        };
+        if collapse {
+            self.collapsed.insert(path);
+        } else {
+            self.collapsed.remove(&path);
+        }
+        let before = self.rows.len();
+        self.group(count);
+        let after = self.rows.len();
+        let members = row + 1;
+        if after > before {
+            self.events.rows_inserted(members..members + after - before);
+        } else if before > after {
+            self.events.rows_removed(members..members + before - after);
+        }
+        // the triangle turned
+        self.events.rows_updated(row..row + 1);
+        true
+    }
+
+    fn toggle(&mut self, row: usize) -> bool {
+        match self.rows.get(row) {
+            Some(GroupRow::Header(group)) => {
+                let collapse = !group.collapsed;
+                self.set_collapsed(row, collapse)
+            }
+            _ => false,
+        }
+    }
+
+    /// Group again if the source changed.
+    fn update(&mut self, count: usize) {
+        if self.dirty || self.stale.swap(false, Ordering::Relaxed) || count != self.source_count {
+            self.group(count);
+        }
+    }
+
+    fn group(&mut self, count: usize) {
+        let mut rows = Vec::new();
+        let source: Vec<usize> = (0..count).collect();
+        self.add_groups(&mut rows, &source, &mut Vec::new());
+        self.rows = Arc::new(rows);
+        self.source_count = count;
+        self.dirty = false;
+    }
+
+    /// Add the groups of some source rows, for the grouped column after `path`.
+    fn add_groups(&mut self, rows: &mut Vec<GroupRow>, source: &[usize], path: &mut Vec<String>) {
+        let Some(&col) = self.group_by.get(path.len()) else {
+            rows.extend(source.iter().map(|r| GroupRow::Row(*r)));
+            return;
+        };
+        // groups in the order their first rows appear
+        let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for &row in source {
+            let cell = self.model.get_cell(row as i32, col as i32);
+            let value = cell.to_text().unwrap_or_default();
+            let i = *index.entry(value.clone()).or_insert_with(|| {
+                groups.push((value, Vec::new()));
+                groups.len() - 1
+            });
+            groups[i].1.push(row);
+        }
+        for (value, members) in groups {
+            path.push(value);
+            let collapsed = self.collapsed.contains(path);
+            let model = &mut self.model;
+            let aggregates = self
+                .aggregates
+                .iter()
+                .filter_map(|(col, aggregate)| {
+                    let cells = members
+                        .iter()
+                        .map(|row| model.get_cell(*row as i32, *col as i32));
+                    Some((*col, aggregate.apply(cells)?))
+                })
+                .collect();
+            rows.push(GroupRow::Header(Group {
+                path: path.clone(),
+                count: members.len(),
+                first: members[0],
+                aggregates,
+                collapsed,
+            }));
+            if !collapsed {
+                self.add_groups(rows, &members, path);
+            }
+            path.pop();
+        }
+    }
+
+    /// Group again if an action on the source model changed it.
+    fn changed(&mut self, action: impl FnOnce(&mut M) -> bool) -> bool {
+        let changed = action(&mut self.model);
+        if changed {
+            self.regroup();
+        }
+        changed
+    }
+
+    fn source(&self, row: i32) -> Option<i32> {
+        self.source_row(row as usize).map(|r| r as i32)
+    }
+}
+
+impl<M: SimpleModel> SimpleModel for GroupedModel<M> {
+    fn row_info(&mut self) -> RowInfo {
+        let row_info = self.model.row_info();
+        self.update(row_info.count);
+        let height = match row_info.height {
+            RowHeight::All(h) => RowHeight::All(h),
+            RowHeight::PerRow(f) => {
+                let rows = self.rows.clone();
+                RowHeight::PerRow(Box::new(move |row| match rows.get(row) {
+                    Some(GroupRow::Row(r)) => f(*r),
+                    Some(GroupRow::Header(group)) => f(group.first),
+                    None => 0,
+                }))
+            }
+        };
+        RowInfo {
+            count: self.rows.len(),
+            height,
+        }
+    }
+
+    fn column_info(&mut self) -> ColumnInfo {
+        self.model.column_info()
+    }
+
+    /// Collapsing and expanding groups, and source model changes reported as `Reset`.
+    fn events(&mut self) -> Option<ModelEvents> {
+        Some(self.events.clone())
+    }
+
+    fn get_cell(&mut self, row: i32, col: i32) -> SimpleCell {
+        match self.rows.get(row as usize) {
+            Some(GroupRow::Row(r)) => self.model.get_cell(*r as i32, col),
+            Some(GroupRow::Header(group)) if col == 0 => SimpleCell::Delegate(Box::new(TreeCell {
+                text: format!("{} ({})", group.path.last().unwrap(), group.count),
+                depth: group.path.len() - 1,
+                has_children: true,
+                expanded: !group.collapsed,
+                bold: true,
+                theme: self.theme,
+            })),
+            Some(GroupRow::Header(group)) => {
+                match group.aggregates.iter().find(|(c, _)| *c == col as usize) {
+                    Some((_, Total::Int(value))) => SimpleCell::Int(*value),
+                    Some((_, Total::Float(value))) => SimpleCell::Float {
+                        value: *value,
+                        precision: 2,
+                    },
+                    None => SimpleCell::None,
+                }
+            }
+            None => SimpleCell::None,
+        }
+    }
+
+    fn hover(&self, row: i32, col: i32) -> Option<String> {
+        self.model.hover(self.source(row)?, col)
+    }
+
+    fn row_header(&mut self, row: i32) -> Option<String> {
+        self.model.row_header(self.source(row)?)
+    }
+
+    fn row_key(&mut self, row: i32) -> Option<u64> {
+        self.model.row_key(self.source(row)?)
+    }
+
+    fn row_style(&mut self, row: i32) -> CellStyle {
+        match self.rows.get(row as usize) {
+            Some(GroupRow::Row(r)) => self.model.row_style(*r as i32),
+            Some(GroupRow::Header(_)) => CellStyle {
+                bold: true,
+                ..Default::default()
+            },
+            None => CellStyle::default(),
+        }
+    }
+
+    fn cell_style(&mut self, row: i32, col: i32) -> CellStyle {
+        match self.source(row) {
+            Some(row) => self.model.cell_style(row, col),
+            None => CellStyle::default(),
+        }
+    }
+
+    fn sort(&mut self, col: usize, order: Order) {
+        self.model.sort(col, order);
+        self.regroup();
+    }
+
+    fn sort_multi(&mut self, keys: &[(usize, Order)]) {
+        self.model.sort_multi(keys);
+        self.regroup();
+    }
+
+    fn is_editable(&self, row: i32, col: i32) -> bool {
+        self.source(row)
+            .is_some_and(|row| self.model.is_editable(row, col))
+    }
+
+    fn editor(&self, row: i32, col: i32) -> EditorKind {
+        match self.source(row) {
+            Some(row) => self.model.editor(row, col),
+            None => EditorKind::Text,
+        }
+    }
+
+    fn set_cell(&mut self, row: i32, col: i32, value: &str) -> Result<(), String> {
+        let row = self.source(row).ok_or("Group headers are read only")?;
+        self.model.set_cell(row, col, value)?;
+        if self.group_by.contains(&(col as usize))
+            || self.aggregates.iter().any(|(c, _)| *c == col as usize)
+        {
+            self.regroup();
+        }
+        Ok(())
+    }
+
+    fn activate(&mut self, row: i32, col: i32) -> bool {
+        match self.source(row) {
+            Some(row) => self.changed(|model| model.activate(row, col)),
+            None => self.toggle(row as usize),
+        }
+    }
+
+    fn expand(&mut self, row: i32, expand: bool) -> bool {
+        match self.source(row) {
+            Some(row) => self.changed(|model| model.expand(row, expand)),
+            None => self.set_collapsed(row as usize, !expand),
+        }
+    }
+
+    fn click(&mut self, row: i32, col: i32) -> bool {
+        match self.source(row) {
+            Some(row) => self.changed(|model| model.click(row, col)),
+            None => self.toggle(row as usize),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::model_events::ModelEvent;
+    use crate::simple_model::{VecColumn, VecModel};
+
+    type Sale = (&'static str, &'static str, i64);
+
+    fn sales() -> GroupedModel<VecModel<Sale>> {
+        let items = vec![
+            ("west", "a", 5),
+            ("east", "b", 1),
+            ("west", "c", 2),
+            ("east", "b", 4),
+            ("west", "a", 3),
+        ];
+        GroupedModel::new(VecModel::new(
+            items,
+            vec![
+                VecColumn::new("Region", 50, |s: &Sale| SimpleCell::Text(s.0.to_string())),
+                VecColumn::new("City", 50, |s: &Sale| SimpleCell::Text(s.1.to_string())),
+                VecColumn::new("Amount", 50, |s: &Sale| SimpleCell::Int(s.2)),
+            ],
+        ))
+    }
+
+    /// Group header paths, or source rows.
+    fn shown(model: &mut GroupedModel<VecModel<Sale>>) -> Vec<String> {
+        let count = model.row_info().count;
+        (0..count)
+            .map(|row| match &model.rows[row] {
+                GroupRow::Header(group) => format!("{}:{}", group.path.join("/"), group.count),
+                GroupRow::Row(r) => r.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ungrouped_rows_pass_through() {
+        let mut model = sales();
+        assert_eq!(shown(&mut model), ["0", "1", "2", "3", "4"]);
+        assert_eq!(model.source_row(2), Some(2));
+    }
+
+    #[test]
+    fn nested_groups_in_first_row_order() {
+        let mut model = sales();
+        model.set_group_by(&[0, 1]);
+        assert_eq!(
+            shown(&mut model),
+            ["west:3", "west/a:2", "0", "4", "west/c:1", "2", "east:2", "east/b:2", "1", "3"]
+        );
+        assert!(model.is_group(0));
+        assert_eq!(model.source_row(0), None);
+        assert_eq!(model.row_key(0), None);
+        assert!(model.set_cell(0, 2, "1").is_err());
+    }
+
+    #[test]
+    fn aggregates() {
+        let mut model = sales();
+        model.set_group_by(&[0]);
+        model.set_aggregate(2, Aggregate::Sum);
+        model.row_info();
+        assert!(matches!(model.get_cell(0, 2), SimpleCell::Int(10)));
+        assert!(matches!(model.get_cell(4, 2), SimpleCell::Int(5)));
+        model.set_aggregate(2, Aggregate::Avg);
+        model.row_info();
+        assert!(
+            matches!(model.get_cell(0, 2), SimpleCell::Float { value, .. } if value == 10.0 / 3.0)
+        );
+        assert!(matches!(model.get_cell(0, 1), SimpleCell::None));
+        model.clear_aggregate(2);
+        model.row_info();
+        assert!(matches!(model.get_cell(0, 2), SimpleCell::None));
+    }
+
+    #[test]
+    fn header_text() {
+        let mut model = sales();
+        model.set_group_by(&[0]);
+        model.row_info();
+        let cell = model.get_cell(0, 0);
+        assert!(matches!(cell, SimpleCell::Delegate(_)));
+        assert_eq!(cell.to_text().as_deref(), Some("west (3)"));
+    }
+
+    #[test]
+    fn aggregate_apply() {
+        let cells = || {
+            vec![
+                SimpleCell::Int(2),
+                SimpleCell::Text("x".to_string()),
+                SimpleCell::Int(-1),
+            ]
+            .into_iter()
+        };
+        assert_eq!(Aggregate::Sum.apply(cells()), Some(Total::Int(1)));
+        assert_eq!(Aggregate::Min.apply(cells()), Some(Total::Int(-1)));
+        assert_eq!(Aggregate::Max.apply(cells()), Some(Total::Int(2)));
+        assert_eq!(Aggregate::Avg.apply(cells()), Some(Total::Float(0.5)));
+        // beyond f64's 53 bits of precision
+        let big = || vec![SimpleCell::Int(i64::MAX - 1), SimpleCell::Int(-2)].into_iter();
+        assert_eq!(Aggregate::Sum.apply(big()), Some(Total::Int(i64::MAX - 3)));
+        let overflow = vec![SimpleCell::Int(i64::MAX), SimpleCell::Int(1)];
+        assert_eq!(
+            Aggregate::Sum.apply(overflow.into_iter()),
+            Some(Total::Int(i64::MAX))
+        );
+        let floats = vec![
+            SimpleCell::Int(1),
+            SimpleCell::Float {
+                value: 0.5,
+                precision: 1,
+            },
+        ];
+        assert_eq!(
+            Aggregate::Sum.apply(floats.into_iter()),
+            Some(Total::Float(1.5))
+        );
+        assert_eq!(Aggregate::Sum.apply(std::iter::empty()), None);
+    }
+
+    #[test]
+    fn collapse_and_expand() {
+        let mut model = sales();
+        model.set_group_by(&[0, 1]);
+        model.row_info();
+        let events: Arc<Mutex<Vec<ModelEvent>>> = Default::default();
+        let log = events.clone();
+        model
+            .events()
+            .unwrap()
+            .subscribe(move |e| log.lock().unwrap().push(e.clone()));
+
+        assert!(model.click(1, 0));
+        assert!(!model.set_collapsed(1, true));
+        assert_eq!(
+            shown(&mut model),
+            ["west:3", "west/a:2", "west/c:1", "2", "east:2", "east/b:2", "1", "3"]
+        );
+        assert!(model.expand(0, false));
+        assert_eq!(
+            shown(&mut model),
+            ["west:3", "east:2", "east/b:2", "1", "3"]
+        );
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ModelEvent::RowsRemoved(2..4),
+                ModelEvent::RowsUpdated(1..2),
+                ModelEvent::RowsRemoved(1..4),
+                ModelEvent::RowsUpdated(0..1),
+            ]
+        );
+        events.lock().unwrap().clear();
+        // inner groups stay collapsed
+        assert!(model.activate(0, 0));
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ModelEvent::RowsInserted(1..4),
+                ModelEvent::RowsUpdated(0..1)
+            ]
+        );
+        assert_eq!(
+            shown(&mut model)[..4],
+            ["west:3", "west/a:2", "west/c:1", "2"]
+        );
+    }
+
+    #[test]
+    fn source_changes_regroup() {
+        let mut model = sales();
+        model.set_group_by(&[0]);
+        assert_eq!(shown(&mut model).len(), 7);
+        model.model.push(("north", "d", 1));
+        assert_eq!(shown(&mut model)[7..], ["north:1", "5"]);
+        model.sort(0, Order::Ascending);
+        assert_eq!(shown(&mut model)[0], "east:2");
+    }
+}
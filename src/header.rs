@@ -0,0 +1,168 @@
+use std::{
+    collections::BTreeMap,
+    ffi::{CStr, CString},
+    sync::Mutex,
+};
+
+use fltk::{
+    app, draw,
+    enums::{Color, Font, Shortcut},
+    menu::{MenuButton, MenuButtonType, MenuFlag},
+    prelude::{MenuExt, WidgetExt},
+};
+
+use crate::column_view::ColumnView;
+use crate::simple_model::{ColumnDetail, Order, SortKeys};
+
+/// Draw the sort arrow, and the sort priority when sorting by more than one column,
+/// at the right edge of a header cell.
+pub(crate) fn draw_sort_indicator(
+    keys: &SortKeys,
+    col: usize,
+    (x, y, w, h): (i32, i32, i32, i32),
+    color: Color,
+) {
+    let Some((priority, order)) = keys.get(col) else {
+        return;
+    };
+    let ax = x + w - 12;
+    let cy = y + h / 2;
+    draw::set_draw_color(color);
+    match order {
+        Order::Ascending => draw::draw_polygon(ax, cy + 3, ax + 8, cy + 3, ax + 4, cy - 3),
+        Order::Descending => draw::draw_polygon(ax, cy - 3, ax + 8, cy - 3, ax + 4, cy + 3),
+        Order::None => {}
+    }
+    if keys.keys.len() > 1 {
+        let (font, size) = (draw::font(), draw::size());
+        draw::set_font(Font::Helvetica, 8);
+        draw::draw_text(&(priority + 1).to_string(), ax - 7, cy + 4);
+        draw::set_font(font, size);
+    }
+}
+
+/// Width left for the text of a header cell, beside the padding and sort indicator.
+pub(crate) fn header_text_width(keys: &SortKeys, col: usize, width: i32) -> i32 {
+    let indicator = match keys.get(col) {
+        Some(_) if keys.keys.len() > 1 => 20,
+        Some(_) => 14,
+        None => 0,
+    };
+    width - 4 - indicator
+}
+
+/// Text cut short with an ellipsis to fit a width in the current font, or None if it already fits.
+pub(crate) fn ellipsize(text: &str, width: i32) -> Option<String> {
+    if draw::width(text) <= width as f64 {
+        return None;
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let shortened = |len: usize| chars[..len].iter().chain(['…'].iter()).collect::<String>();
+    // longest prefix that fits beside the ellipsis
+    let (mut low, mut high) = (0, chars.len());
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if draw::width(&shortened(mid)) <= width as f64 {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Some(shortened(low))
+}
+
+/// Tooltip text that lives as long as the program, since FLTK keeps a pointer to it.
+/// Each distinct text is kept once, so hovering over headers does not grow memory.
+pub(crate) fn static_text(text: &str) -> &'static CStr {
+    static TEXTS: Mutex<BTreeMap<String, &'static CStr>> = Mutex::new(BTreeMap::new());
+    let mut texts = TEXTS.lock().unwrap();
+    if let Some(text) = texts.get(text) {
+        return text;
+    }
+    let c_text = CString::new(text.replace('\0', "")).unwrap();
+    let c_text: &'static CStr = Box::leak(c_text.into_boxed_c_str());
+    texts.insert(text.to_string(), c_text);
+    c_text
+}
+
+/// Choices from the header context menu.
+pub(crate) enum HeaderAction {
+    Sort(Order),
+    Autofit,
+    Reset,
+    /// hide or show a model column
+    Toggle(usize),
+}
+
+/// Pop up the header context menu at the mouse.  Sorting and autofit apply to the clicked column,
+/// and every column is listed with a check box to hide or show it.
+/// The view is a copy, so the table can lock its own view to redraw while the menu is open.
+pub(crate) fn header_menu(details: &[ColumnDetail], columns: &ColumnView) -> Option<HeaderAction> {
+    let mut menu = MenuButton::default().with_type(MenuButtonType::Popup3);
+    let mut actions = Vec::new();
+    let mut add = |menu: &mut MenuButton, label: &str, flag: MenuFlag, action: HeaderAction| {
+        let index = menu.add(label, Shortcut::None, flag, |_| {});
+        actions.push((index, action));
+    };
+    add(
+        &mut menu,
+        "Sort ascending",
+        MenuFlag::Normal,
+        HeaderAction::Sort(Order::Ascending),
+    );
+    add(
+        &mut menu,
+        "Sort descending",
+        MenuFlag::Normal,
+        HeaderAction::Sort(Order::Descending),
+    );
+    add(
+        &mut menu,
+        "Autofit width",
+        MenuFlag::Normal,
+        HeaderAction::Autofit,
+    );
+    add(
+        &mut menu,
+        "Reset columns",
+        MenuFlag::MenuDivider,
+        HeaderAction::Reset,
+    );
+    for col in columns.order() {
+        let flag = if columns.is_hidden(*col) {
+            MenuFlag::Toggle
+        } else if columns.displayed().len() > 1 {
+            MenuFlag::Toggle | MenuFlag::Value
+        } else {
+            // keep at least one column
+            MenuFlag::Toggle | MenuFlag::Value | MenuFlag::Inactive
+        };
+        let label = menu_label(&details[*col].header);
+        add(&mut menu, &label, flag, HeaderAction::Toggle(*col));
+    }
+    let chosen = menu.popup();
+    let action = chosen.and_then(|item| {
+        actions
+            .into_iter()
+            .find(|(index, _)| menu.at(*index).is_some_and(|i| i == item))
+            .map(|(_, action)| action)
+    });
+    app::delete_widget(menu);
+    action
+}
+
+/// Escape the characters that menus treat as shortcuts, submenus and dividers.
+fn menu_label(header: &str) -> String {
+    let mut label = String::with_capacity(header.len());
+    for c in header.chars() {
+        match c {
+            '\\' | '/' | '_' => {
+                label.push('\\');
+                label.push(c);
+            }
+            '&' => label.push_str("&&"),
+            c => label.push(c),
+        }
+    }
+    label
+}
@@ -0,0 +1,238 @@
+use std::ops::Range;
+
+use crate::simple_model::{SimpleCell, SimpleModel};
+
+/// Renders a model as a text grid, without a display.
+/// Pixel sizes from the model are converted to characters and lines so that layout regressions
+/// (column widths, row heights, wrapping) show up in snapshot tests.
+pub struct TextRenderer {
+    /// pixels per character
+    pub char_width: u32,
+    /// pixels per line of text
+    pub line_height: u32,
+}
+
+impl Default for TextRenderer {
+    fn default() -> Self {
+        TextRenderer {
+            char_width: 8,
+            line_height: 20,
+        }
+    }
+}
+
+impl TextRenderer {
+    pub fn new(char_width: u32, line_height: u32) -> TextRenderer {
+        TextRenderer {
+            char_width: char_width.max(1),
+            line_height: line_height.max(1),
+        }
+    }
+
+    /// Render the header and every row of the model.
+    pub fn render<T: SimpleModel + ?Sized>(&self, model: &mut T) -> String {
+        let count = model.row_info().count;
+        self.render_rows(model, 0..count)
+    }
+
+    /// Render the header and a range of rows.
+    pub fn render_rows<T: SimpleModel + ?Sized>(
+        &self,
+        model: &mut T,
+        rows: Range<usize>,
+    ) -> String {
+        let row_info = model.row_info();
+        let widths: Vec<usize> = model
+            .column_info()
+            .details
+            .iter()
+            .map(|d| self.chars(d.width))
+            .collect();
+        let headers: Vec<String> = model
+            .column_info()
+            .details
+            .into_iter()
+            .map(|d| d.header)
+            .collect();
+
+        let mut out = String::new();
+        separator(&mut out, &widths, '-');
+        let header_lines: Vec<Vec<String>> = headers
+            .iter()
+            .zip(&widths)
+            .map(|(h, w)| vec![truncate(h, *w)])
+            .collect();
+        grid_line(&mut out, &widths, &header_lines, 0);
+        separator(&mut out, &widths, '=');
+
+        let rows = rows.start.min(row_info.count)..rows.end.min(row_info.count);
+        for row in rows {
+            let lines = self.lines(row_info.height.for_row(row as u32));
+            let cells: Vec<Vec<String>> = widths
+                .iter()
+                .enumerate()
+                .map(|(col, w)| {
+                    let text = cell_text(&model.get_cell(row as i32, col as i32));
+                    wrap(&text, *w)
+                })
+                .collect();
+            for line in 0..lines {
+                grid_line(&mut out, &widths, &cells, line);
+            }
+            separator(&mut out, &widths, '-');
+        }
+        out
+    }
+
+    fn chars(&self, width: u32) -> usize {
+        (width / self.char_width).max(1) as usize
+    }
+
+    fn lines(&self, height: u32) -> usize {
+        (height / self.line_height).max(1) as usize
+    }
+}
+
+/// Placeholder text for cells that can only be painted.
+fn cell_text(cell: &SimpleCell) -> String {
+    if let Some(text) = cell.to_text() {
+        return text;
+    }
+    match cell {
+        SimpleCell::Image(_) => "<image>".to_string(),
+        SimpleCell::Delegate(_) => "<delegate>".to_string(),
+        SimpleCell::Widget(_) => "<widget>".to_string(),
+        _ => String::new(),
+    }
+}
+
+fn separator(out: &mut String, widths: &[usize], fill: char) {
+    out.push('+');
+    for w in widths {
+        out.extend(std::iter::repeat_n(fill, *w));
+        out.push('+');
+    }
+    out.push('\n');
+}
+
+fn grid_line(out: &mut String, widths: &[usize], cells: &[Vec<String>], line: usize) {
+    out.push('|');
+    for (w, cell) in widths.iter().zip(cells) {
+        let txt = cell.get(line).map(|s| s.as_str()).unwrap_or("");
+        out.push_str(txt);
+        out.extend(std::iter::repeat_n(' ', w - txt.chars().count()));
+        out.push('|');
+    }
+    out.push('\n');
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    text.lines()
+        .next()
+        .unwrap_or("")
+        .chars()
+        .take(width)
+        .collect()
+}
+
+/// Word wrap to the column width, honoring embedded newlines.  Words longer than the column are split.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let mut word: Vec<char> = word.chars().collect();
+            let used = line.chars().count();
+            if used > 0 && used + 1 + word.len() > width {
+                lines.push(std::mem::take(&mut line));
+            } else if used > 0 {
+                line.push(' ');
+            }
+            while word.len() > width {
+                let rest = word.split_off(width);
+                lines.push(word.into_iter().collect());
+                word = rest;
+            }
+            line.extend(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_model::{ColumnDetail, ColumnInfo, RowHeight, RowInfo};
+
+    struct Grid;
+    impl SimpleModel for Grid {
+        fn row_info(&mut self) -> RowInfo {
+            RowInfo {
+                count: 2,
+                height: RowHeight::PerRow(Box::new(|r| if r == 1 { 40 } else { 20 })),
+            }
+        }
+        fn column_info(&mut self) -> ColumnInfo {
+            ColumnInfo {
+                details: vec![
+                    ColumnDetail {
+                        header: "Name".to_string(),
+                        width: 48,
+                    },
+                    ColumnDetail {
+                        header: "Count".to_string(),
+                        width: 24,
+                    },
+                ],
+            }
+        }
+        fn get_cell(&mut self, row: i32, col: i32) -> SimpleCell {
+            match (row, col) {
+                (0, 0) => SimpleCell::Text("short".to_string()),
+                (1, 0) => SimpleCell::Text("a longer name".to_string()),
+                (r, _) => SimpleCell::Int(r as i64 * 10),
+            }
+        }
+    }
+
+    #[test]
+    fn render_grid() {
+        let text = TextRenderer::default().render(&mut Grid);
+        assert_eq!(
+            text,
+            "\
++------+---+
+|Name  |Cou|
++======+===+
+|short |0  |
++------+---+
+|a     |10 |
+|longer|   |
++------+---+
+"
+        );
+    }
+
+    #[test]
+    fn render_rows_clamps_range() {
+        let text = TextRenderer::default().render_rows(&mut Grid, 1..10);
+        assert_eq!(text.lines().count(), 3 + 3);
+        assert!(!text.contains("short"));
+    }
+
+    #[test]
+    fn wrap_words() {
+        assert_eq!(wrap("one two three", 7), vec!["one two", "three"]);
+        assert_eq!(wrap("a\nb", 5), vec!["a", "b"]);
+        assert_eq!(wrap("abcdefgh", 3), vec!["abc", "def", "gh"]);
+        assert_eq!(wrap("", 3), vec![""]);
+    }
+
+    #[test]
+    fn truncate_first_line() {
+        assert_eq!(truncate("header\nsecond", 3), "hea");
+        assert_eq!(truncate("ab", 5), "ab");
+        assert_eq!(truncate("", 5), "");
+    }
+}
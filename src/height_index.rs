@@ -0,0 +1,205 @@
+use std::ops::Range;
+
+use crate::model_events::ModelEvent;
+use crate::simple_model::{RowHeight, RowInfo};
+
+/// Cached row positions, so finding where a row is, or which row is at a position, is O(log n) even
+/// when every row has its own height.
+#[derive(Debug, Default)]
+pub(crate) struct HeightIndex {
+    /// height of every row, or None if rows have their own heights
+    uniform: Option<u32>,
+    /// bottom of each row, for rows with their own heights
+    ends: Vec<u64>,
+    count: usize,
+}
+
+impl HeightIndex {
+    /// Catch up with the model.  Added rows are measured, and rows forgotten since the last update are
+    /// measured again.
+    pub fn update(&mut self, row_info: &RowInfo) {
+        match &row_info.height {
+            RowHeight::All(h) => {
+                self.uniform = Some(*h);
+                self.ends.clear();
+            }
+            RowHeight::PerRow(f) => {
+                if self.uniform.take().is_some() {
+                    self.ends.clear();
+                }
+                self.ends.truncate(row_info.count);
+                let mut end = self.ends.last().copied().unwrap_or_default();
+                for row in self.ends.len()..row_info.count {
+                    end += f(row) as u64;
+                    self.ends.push(end);
+                }
+            }
+        }
+        self.count = row_info.count;
+    }
+
+    /// Measure rows again from a row.
+    pub fn forget(&mut self, row: usize) {
+        self.ends.truncate(row);
+    }
+
+    /// Measure every row again, as when rows were sorted or filtered.
+    pub fn clear(&mut self) {
+        self.ends.clear();
+    }
+
+    /// Forget the rows moved or changed by a model event.
+    pub fn apply(&mut self, event: &ModelEvent) {
+        match event {
+            ModelEvent::RowsInserted(rows)
+            | ModelEvent::RowsRemoved(rows)
+            | ModelEvent::RowsUpdated(rows) => self.forget(rows.start),
+            ModelEvent::ColumnsChanged => {}
+            ModelEvent::Reset => self.clear(),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Top of a row, relative to the first row.
+    pub fn row_y(&self, row: usize) -> i32 {
+        let row = row.min(self.count);
+        let y = match self.uniform {
+            Some(h) => row as u64 * h as u64,
+            None => match row {
+                0 => 0,
+                _ => self.ends[row - 1],
+            },
+        };
+        y.min(i32::MAX as u64) as i32
+    }
+
+    pub fn row_height(&self, row: usize) -> i32 {
+        if row >= self.count {
+            return 0;
+        }
+        self.row_y(row + 1) - self.row_y(row)
+    }
+
+    /// Height of all rows.
+    pub fn total(&self) -> i32 {
+        self.row_y(self.count)
+    }
+
+    /// Row at a position relative to the first row, or the row count if the position is below the last row.
+    pub fn row_at(&self, y: i32) -> usize {
+        let y = y.max(0) as u64;
+        match self.uniform {
+            Some(0) => self.count,
+            Some(h) => ((y / h as u64) as usize).min(self.count),
+            None => self.ends.partition_point(|end| *end <= y),
+        }
+    }
+
+    /// Rows at least partially inside a range of positions.
+    pub fn rows_between(&self, top: i32, bottom: i32) -> Range<usize> {
+        let first = self.row_at(top);
+        let last = if bottom > top {
+            (self.row_at(bottom - 1) + 1).min(self.count)
+        } else {
+            first
+        };
+        first..last.max(first)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    const ROWS: usize = 1_000_000;
+
+    fn log_height(row: usize) -> u32 {
+        if row.is_multiple_of(10) {
+            40
+        } else {
+            20
+        }
+    }
+
+    /// Rows of `log_height`, counting how many rows are measured.
+    fn per_row(count: usize, measured: &Rc<Cell<usize>>) -> RowInfo {
+        let measured = measured.clone();
+        RowInfo {
+            count,
+            height: RowHeight::PerRow(Box::new(move |row| {
+                measured.set(measured.get() + 1);
+                log_height(row)
+            })),
+        }
+    }
+
+    #[test]
+    fn million_rows() {
+        let measured = Rc::new(Cell::new(0));
+        let mut index = HeightIndex::default();
+        index.update(&per_row(ROWS, &measured));
+        assert_eq!(measured.get(), ROWS);
+        assert_eq!(index.total(), (ROWS / 10 * (40 + 9 * 20)) as i32);
+
+        // lookups use the index, rather than measuring rows
+        let mut y = 0;
+        for row in 0..ROWS {
+            assert_eq!(index.row_y(row), y);
+            assert_eq!(index.row_at(y), row);
+            let h = log_height(row) as i32;
+            assert_eq!(index.row_at(y + h - 1), row);
+            assert_eq!(index.row_height(row), h);
+            y += h;
+        }
+        assert_eq!(index.row_at(y), ROWS);
+        assert_eq!(measured.get(), ROWS);
+
+        // appended and forgotten rows are the only ones measured again
+        index.update(&per_row(ROWS + 5, &measured));
+        assert_eq!(measured.get(), ROWS + 5);
+        index.apply(&ModelEvent::RowsUpdated(ROWS - 10..ROWS - 9));
+        index.update(&per_row(ROWS + 5, &measured));
+        assert_eq!(measured.get(), ROWS + 5 + 15);
+    }
+
+    #[test]
+    fn uniform_rows() {
+        let mut index = HeightIndex::default();
+        index.update(&RowInfo {
+            count: 10,
+            height: RowHeight::All(20),
+        });
+        assert_eq!(index.row_y(3), 60);
+        assert_eq!(index.row_at(59), 2);
+        assert_eq!(index.row_at(1000), 10);
+        assert_eq!(index.rows_between(30, 70), 1..4);
+        assert_eq!(index.rows_between(190, 400), 9..10);
+        assert_eq!(index.rows_between(50, 50), 2..2);
+        assert_eq!(index.row_height(10), 0);
+    }
+
+    #[test]
+    fn switching_height_kinds() {
+        let measured = Rc::new(Cell::new(0));
+        let mut index = HeightIndex::default();
+        index.update(&per_row(20, &measured));
+        index.update(&RowInfo {
+            count: 20,
+            height: RowHeight::All(5),
+        });
+        assert_eq!(index.total(), 100);
+        index.update(&per_row(20, &measured));
+        assert_eq!(index.total(), 2 * 40 + 18 * 20);
+        // removed rows are forgotten
+        index.update(&per_row(11, &measured));
+        assert_eq!(index.total(), 2 * 40 + 9 * 20);
+        index.apply(&ModelEvent::Reset);
+        index.update(&per_row(11, &measured));
+        assert_eq!(measured.get(), 20 + 20 + 11);
+    }
+}
@@ -1,32 +1,77 @@
 use std::{
-    cmp::Ordering,
+    io::{self, Write},
     ops::{Deref, DerefMut, Range},
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, MutexGuard},
 };
 
 use fltk::{
-    app::{self, set_font},
-    draw::{
-        self, draw_frame, draw_rect_fill, draw_text2, font, pop_clip, push_clip, set_draw_color,
-    },
-    enums::{Align, Color, Event, EventState, Font},
+    app::{self, MouseButton},
+    dialog,
+    draw::{self, draw_rect_fill, draw_text2, pop_clip, push_clip, set_draw_color},
+    enums::{Align, CallbackTrigger, Cursor, Damage, Event, EventState, Font, Key},
     frame::Frame,
     group::{Group, Pack, PackType, Scroll},
-    prelude::{GroupExt, WidgetBase, WidgetExt},
+    input::Input,
+    misc::Tooltip,
+    prelude::{GroupExt, InputExt, ValuatorExt, WidgetBase, WidgetExt},
 };
 use timer::Guard;
 
-use crate::simple_model::{RowHeight, SimpleCell, SimpleModel};
+use crate::cell_draw::draw_value;
+use crate::cell_editor::{CellEditor, EditorSlot};
+use crate::column_view::{fit_width, ColumnView};
+use crate::cursor::{is_enter, CellCursor};
+use crate::filtered_model::FilteredModel;
+use crate::header::{
+    draw_sort_indicator, ellipsize, header_menu, header_text_width, static_text, HeaderAction,
+};
+use crate::height_index::HeightIndex;
+use crate::model_events::{subscribe_ui, ModelEvent};
+use crate::selection::{SelectionMode, SelectionModel};
+use crate::simple_model::{
+    export_cells, ColumnDetail, ExportRows, Exporter, Order, SimpleCell, SimpleModel, SortKeys,
+    TsvExporter,
+};
+use crate::theme::TableTheme;
+use crate::view_state::TableViewState;
+
+/// Distance from a column border that starts a resize.
+const RESIZE_MARGIN: i32 = 3;
+/// Width of the row header, when shown.
+const ROW_HEADER_WIDTH: i32 = 50;
 
 pub struct JoeTable<T: SimpleModel + 'static> {
     pack: Pack,
     header: Frame,
+    /// row header beside the scroll
+    body: Pack,
+    row_header: Frame,
     scroll: Scroll,
     table: Group,
     pub model: Arc<Mutex<T>>,
-    pub selection: Arc<Mutex<Range<usize>>>,
-    font: Font,
-    font_size: i32,
+    pub selection: Arc<Mutex<SelectionModel>>,
+    theme: Arc<Mutex<TableTheme>>,
+    copy_headers: Arc<Mutex<bool>>,
+    editor: EditorSlot,
+    sort_keys: Arc<Mutex<SortKeys>>,
+    columns: Arc<Mutex<ColumnView>>,
+    cursor: Arc<Mutex<CellCursor>>,
+    heights: Arc<Mutex<HeightIndex>>,
+    frozen: Arc<Mutex<Frozen>>,
+    quick_filter: Option<Input>,
+}
+
+/// Leading rows and displayed columns that stay in place while the rest scroll.
+#[derive(Debug, Clone, Copy, Default)]
+struct Frozen {
+    rows: usize,
+    cols: usize,
+}
+
+impl Frozen {
+    fn any(&self) -> bool {
+        self.rows > 0 || self.cols > 0
+    }
 }
 
 impl<T: SimpleModel + 'static> Clone for JoeTable<T> {
@@ -34,12 +79,21 @@ impl<T: SimpleModel + 'static> Clone for JoeTable<T> {
         Self {
             pack: self.pack.clone(),
             header: self.header.clone(),
+            body: self.body.clone(),
+            row_header: self.row_header.clone(),
             scroll: self.scroll.clone(),
             table: self.table.clone(),
             model: self.model.clone(),
             selection: self.selection.clone(),
-            font: self.font,
-            font_size: 10,
+            theme: self.theme.clone(),
+            copy_headers: self.copy_headers.clone(),
+            editor: self.editor.clone(),
+            sort_keys: self.sort_keys.clone(),
+            columns: self.columns.clone(),
+            cursor: self.cursor.clone(),
+            heights: self.heights.clone(),
+            frozen: self.frozen.clone(),
+            quick_filter: self.quick_filter.clone(),
         }
     }
 }
@@ -62,50 +116,151 @@ where
     pub fn new(model: T) -> Self {
         let pack = Pack::default_fill().with_type(PackType::Vertical);
         let header = Frame::default_fill();
+        let body = Pack::default_fill().with_type(PackType::Horizontal);
+        let mut row_header = Frame::default().with_size(ROW_HEADER_WIDTH, 0);
+        row_header.hide();
         let scroll = Scroll::default_fill();
         let mut table = Group::default_fill();
         scroll.end();
-        pack.resizable(&scroll);
+        body.resizable(&scroll);
+        body.end();
+        pack.resizable(&body);
         pack.end();
         let model = Arc::new(Mutex::new(model));
         let mut this = Self {
             pack,
             header,
+            body,
+            row_header,
             scroll,
             table: table.clone(),
             model: model.clone(),
             selection: Default::default(),
-            font: Font::Helvetica,
-            font_size: 12,
+            theme: Default::default(),
+            copy_headers: Default::default(),
+            editor: Default::default(),
+            sort_keys: Default::default(),
+            columns: Default::default(),
+            cursor: Default::default(),
+            heights: Default::default(),
+            frozen: Default::default(),
+            quick_filter: None,
         };
         {
             let model = model.clone();
             let mut this: JoeTable<T> = this.clone();
-            table.handle(move |_table, e| {
-                if Event::Released == e {
+            table.handle(move |table, e| match e {
+                Event::Push | Event::Released if CellEditor::event_inside(&this.editor) => false,
+                Event::Released => {
                     if let Some((row, col)) = this.pos_to_row_col(app::event_x(), app::event_y()) {
-                        match model.lock().unwrap().get_cell(row, col) {
-                            SimpleCell::Widget(mut w) => {
-                                w.do_callback();
-                            }
-                            _ => {
-                                if app::event_state().contains(EventState::Shift) {
-                                    // range select
-                                    let mut selection = this.selection.lock().unwrap();
-                                    if selection.is_empty() {
-                                        selection.start = row as usize;
-                                    }
-                                    selection.end = row as usize + 1;
-                                } else {
-                                    // single select
-                                    this.select_row(row as usize);
-                                }
-                            }
+                        if let SimpleCell::Widget(mut w) = model.lock().unwrap().get_cell(row, col)
+                        {
+                            w.do_callback();
                         }
                         return true;
                     }
+                    false
                 }
-                false
+                Event::Push => {
+                    // needed for keyboard events
+                    let _ = table.take_focus();
+                    if let Some((row, col)) = this.pos_to_row_col(app::event_x(), app::event_y()) {
+                        if this.click(row, col) {
+                            return true;
+                        }
+                        let display_col = this.columns.lock().unwrap().display_col(col as usize);
+                        let display_col = display_col.unwrap_or_default();
+                        let state = app::event_state();
+                        this.cursor.lock().unwrap().set(row, display_col as i32);
+                        this.selection.lock().unwrap().click(
+                            row as usize,
+                            display_col,
+                            state.contains(EventState::Command),
+                            state.contains(EventState::Shift),
+                        );
+                        this.selection_changed();
+                        if app::event_clicks() {
+                            // double click to edit
+                            this.edit_cell(row, col);
+                        }
+                    }
+                    true
+                }
+                // drag to select
+                Event::Drag => {
+                    if let Some((row, col)) = this.pos_to_row_col(app::event_x(), app::event_y()) {
+                        let display_col = this.columns.lock().unwrap().display_col(col as usize);
+                        let display_col = display_col.unwrap_or_default();
+                        this.cursor.lock().unwrap().set(row, display_col as i32);
+                        this.selection
+                            .lock()
+                            .unwrap()
+                            .extend_to(row as usize, display_col);
+                        this.selection_changed();
+                    }
+                    true
+                }
+                Event::KeyDown if app::event_key() == Key::F2 => {
+                    let cursor = *this.cursor.lock().unwrap();
+                    let row = cursor.row;
+                    let col = {
+                        let model = model.lock().unwrap();
+                        let columns = this.columns.lock().unwrap();
+                        // the focused cell, or the first editable cell in the row
+                        let focused = columns.model_col(cursor.col as usize);
+                        focused
+                            .into_iter()
+                            .chain(columns.displayed().iter().copied())
+                            .map(|c| c as i32)
+                            .find(|c| model.is_editable(row, *c))
+                    };
+                    match col {
+                        Some(col) if !this.selection.lock().unwrap().is_empty() => {
+                            this.edit_cell(row, col)
+                        }
+                        _ => false,
+                    }
+                }
+                Event::KeyDown if is_enter(app::event_key()) => {
+                    let cursor = *this.cursor.lock().unwrap();
+                    this.activate(cursor.row, cursor.col as usize)
+                }
+                Event::Focus | Event::Unfocus => {
+                    // show or hide the focus rectangle
+                    this.body.redraw();
+                    true
+                }
+                // expand or collapse a tree row
+                Event::KeyDown if matches!(app::event_text().as_str(), "+" | "-") => {
+                    let row = this.cursor.lock().unwrap().row;
+                    this.expand(row, app::event_text() == "+")
+                }
+                Event::KeyDown if app::event_state().contains(EventState::Command) => {
+                    match app::event_key() {
+                        k if k == Key::from_char('c') => {
+                            this.copy_selection();
+                            true
+                        }
+                        k if k == Key::from_char('v') => {
+                            app::paste_text(table);
+                            true
+                        }
+                        k if k == Key::from_char('a') => {
+                            let rows = model.lock().unwrap().row_info().count;
+                            let cols = this.columns.lock().unwrap().displayed().len();
+                            this.selection.lock().unwrap().select_all(rows, cols);
+                            this.selection_changed();
+                            true
+                        }
+                        _ => false,
+                    }
+                }
+                Event::KeyDown => this.navigate(app::event_key()),
+                Event::Paste => {
+                    this.paste(&app::event_text());
+                    true
+                }
+                _ => false,
             });
         }
         this.init();
@@ -113,25 +268,41 @@ where
     }
 
     pub fn set_font(&mut self, font: Font, font_size: i32) {
-        self.font = font;
-        self.font_size = font_size;
+        let mut theme = self.theme.lock().unwrap();
+        theme.font = font;
+        theme.font_size = font_size;
+        self.pack.redraw();
+    }
+
+    pub fn theme(&self) -> TableTheme {
+        *self.theme.lock().unwrap()
+    }
+
+    pub fn set_theme(&mut self, theme: TableTheme) {
+        *self.theme.lock().unwrap() = theme;
+        self.scroll.set_color(theme.background);
+        self.pack.redraw();
     }
 
     /// Redraw using a timer.  When the table is dropped, the timer task will be dropped.
-    /// The Timer is passed in, so multiple events can share the timer.
-    pub fn redraw_on(&self, timer: &timer::Timer, duration: chrono::Duration) {
+    /// The Timer is passed in, so multiple events can share the timer.  Rows added since the last tick are
+    /// measured, but rows whose height changed need `redraw_on_events`.
+    pub fn redraw_on(&self, timer: &timer::Timer, duration: chrono::Duration)
+    where
+        T: Send,
+    {
         let guard: Arc<Mutex<Option<Guard>>> = Arc::new(Mutex::new(None));
-        let table = self.table.clone();
-        let mut scroll = self.scroll.clone();
-        let mut header = self.header.clone();
+        let mut this = self.clone();
         guard
             .clone()
             .lock()
             .unwrap()
             .replace(timer.schedule_repeating(duration, move || {
-                if table.visible_r() {
-                    header.redraw();
-                    scroll.redraw();
+                if this.table.visible_r() {
+                    this.follow_rows(None);
+                    SelectionModel::notify_ui(&this.selection);
+                    this.header.redraw();
+                    this.body.redraw();
                     fltk::app::awake();
                 } else {
                     // No longer visible, so stop timer
@@ -140,233 +311,1236 @@ where
             }));
     }
 
+    /// Redraw when the model reports changes with `SimpleModel::events`.  Updated rows are repainted, and
+    /// other changes refresh the whole table.  Returns false if the model doesn't report changes, so
+    /// `redraw_on` is needed instead.
+    pub fn redraw_on_events(&self) -> bool
+    where
+        T: Send,
+    {
+        let Some(events) = self.model.lock().unwrap().events() else {
+            return false;
+        };
+        let mut this = self.clone();
+        subscribe_ui(&events, move |events| {
+            if this.table.was_deleted() {
+                return;
+            }
+            {
+                let mut heights = this.heights.lock().unwrap();
+                events.iter().for_each(|event| heights.apply(event));
+            }
+            if events.iter().all(ModelEvent::is_update) {
+                for event in events {
+                    if let ModelEvent::RowsUpdated(rows) = event {
+                        this.damage_rows(rows);
+                    }
+                }
+            } else {
+                this.follow_rows(None);
+                SelectionModel::notify(&this.selection);
+                this.header.redraw();
+                this.body.redraw();
+            }
+        });
+        true
+    }
+
+    /// Repaint the visible part of some rows.
+    fn damage_rows(&mut self, rows: Range<usize>) {
+        let frozen_rows = self.frozen.lock().unwrap().rows;
+        let visible = self.visible_rows();
+        // frozen rows don't scroll
+        for (shown, top) in [(0..frozen_rows, self.scroll.y()), (visible, self.table.y())] {
+            let (first, end) = (rows.start.max(shown.start), rows.end.min(shown.end));
+            if first >= end {
+                continue;
+            }
+            let (y, h) = {
+                let mut model = self.model.lock().unwrap();
+                let heights = Self::heights(&mut model, &self.heights);
+                let y = heights.row_y(first);
+                (y, heights.row_y(end) - y)
+            };
+            let (x, w) = (self.scroll.x(), self.scroll.w());
+            self.table.set_damage_area(Damage::All, x, top + y, w, h);
+        }
+    }
+
     /// This is done implicitely, but may need to be reinitialized if the model changes.
     pub fn init(&mut self) {
         self.init_header();
+        self.init_row_header();
         self.init_table();
     }
 
     fn init_table(&mut self) {
         let scroll = self.scroll.clone();
         let model = self.model.clone();
-        let mut header = self.header.clone();
-        let new_font = self.font;
-        let new_font_size = self.font_size;
+        let header = self.header.clone();
+        let row_header = self.row_header.clone();
+        let theme = self.theme.clone();
         let selection = self.selection.clone();
+        let columns = self.columns.clone();
+        let cursor = self.cursor.clone();
+        let heights = self.heights.clone();
+        let frozen = self.frozen.clone();
+        // Scroll copies the content that stays visible, so frozen cells are redrawn instead.
+        // The header is outside the scroll, so it follows horizontal scrolling here.
+        for vertical in [true, false] {
+            let mut scroll = scroll.clone();
+            let mut header = header.clone();
+            let mut row_header = row_header.clone();
+            let frozen = frozen.clone();
+            let mut bar = match vertical {
+                true => self.scroll.scrollbar(),
+                false => self.scroll.hscrollbar(),
+            };
+            bar.set_callback(move |bar| {
+                let value = bar.value() as i32;
+                match vertical {
+                    true => {
+                        scroll.scroll_to(scroll.xposition(), value);
+                        row_header.redraw();
+                    }
+                    false => {
+                        scroll.scroll_to(value, scroll.yposition());
+                        header.redraw();
+                    }
+                }
+                if frozen.lock().unwrap().any() {
+                    scroll.redraw();
+                }
+            });
+        }
         self.table.draw(move |table| {
+            let theme = *theme.lock().unwrap();
             let mut model = model.lock().unwrap();
-            let row_info = model.row_info();
-            let row_count = row_info.count as i32;
+            let mut columns = columns.lock().unwrap();
+            let heights = Self::heights(&mut model, &heights);
 
             {
                 // calculate total size for the scrolbar
-                let width = model
-                    .column_info()
-                    .details
-                    .iter()
-                    .map(|d| d.width as i32)
-                    .sum();
-                let height = row_info.height.for_range(0..row_count as u32);
-                table.set_size(width, height as i32);
+                let details = model.column_info().details;
+                columns.update(details.len());
+                let width = columns.total_width(&details) as i32;
+                table.set_size(width, heights.total());
             }
 
-            // calculate which rows need redrawn
-            let (first_row, last_row) = match row_info.height {
-                RowHeight::All(h) => {
-                    let h = h as i32;
-                    let first = scroll.yposition() / h;
-                    let last = 2 + first + scroll.height() / h;
-                    (first, i32::min(row_count, last))
-                }
-                RowHeight::PerRow(f) => {
-                    let mut first = 0;
-                    let mut h = scroll.yposition();
-                    while h > table.y() {
-                        h -= f(first) as i32;
-                        first += 1;
-                    }
-                    let mut last = first;
-                    let mut h = scroll.height();
-                    while h >= table.y() {
-                        h -= f(last) as i32;
-                        last += 1;
-                    }
-                    (first as i32, last as i32)
+            let frozen = *frozen.lock().unwrap();
+            let (sx, sy) = (scroll.x(), scroll.y());
+            let (view_w, view_h) = view_size(&scroll);
+            let (xpos, ypos) = (scroll.xposition(), scroll.yposition());
+            // left of each displayed column, and the right of the last
+            let details = model.column_info().details;
+            let col_xs: Vec<i32> = std::iter::once(0)
+                .chain(columns.displayed().iter().scan(0, |x, c| {
+                    *x += columns.width(*c, details[*c].width) as i32;
+                    Some(*x)
+                }))
+                .collect();
+            let frozen_cols = frozen.cols.min(col_xs.len() - 1);
+            let frozen_rows = frozen.rows.min(heights.count());
+            let (frozen_w, frozen_h) = (col_xs[frozen_cols], heights.row_y(frozen_rows));
+
+            // calculate which rows and columns need redrawn
+            let rows = Self::visible_range(&heights, &scroll, frozen_rows);
+            let first_col = col_xs.partition_point(|x| *x <= xpos + frozen_w).max(1) - 1;
+            let last_col = col_xs
+                .partition_point(|x| *x < xpos + view_w)
+                .min(col_xs.len() - 1);
+            let cols = first_col.max(frozen_cols)..last_col.max(frozen_cols);
+            let selection = selection.lock().unwrap();
+            let focused = table.has_focus().then(|| *cursor.lock().unwrap());
+
+            // scrolled cells, then frozen rows, frozen columns and the corner, as
+            // (rows, columns, scroll offset, clip)
+            let regions = [
+                (
+                    rows.clone(),
+                    cols.clone(),
+                    (xpos, ypos),
+                    (frozen_w, frozen_h, view_w - frozen_w, view_h - frozen_h),
+                ),
+                (
+                    0..frozen_rows,
+                    cols,
+                    (xpos, 0),
+                    (frozen_w, 0, view_w - frozen_w, frozen_h),
+                ),
+                (
+                    rows,
+                    0..frozen_cols,
+                    (0, ypos),
+                    (0, frozen_h, frozen_w, view_h - frozen_h),
+                ),
+                (
+                    0..frozen_rows,
+                    0..frozen_cols,
+                    (0, 0),
+                    (0, 0, frozen_w, frozen_h),
+                ),
+            ];
+            for (rows, cols, (dx, dy), (cx, cy, cw, ch)) in regions {
+                if rows.is_empty() || cols.is_empty() || cw <= 0 || ch <= 0 {
+                    continue;
                 }
-            };
+                push_clip(sx + cx, sy + cy, cw, ch);
+                for row in rows.start as i32..rows.end as i32 {
+                    let height = heights.row_height(row as usize);
+                    let y = sy + heights.row_y(row as usize) - dy;
 
-            for row in first_row..last_row {
-                let mut x = table.x();
-                let height = Self::row_height(&mut model, row);
-                let y = table.y() + Self::row_y(&mut model, row);
+                    let row_style = model.row_style(row);
 
-                let selected = selection.lock().unwrap().contains(&(row as usize));
-                let bg_color = if selected {
-                    Color::Blue.inactive()
-                } else {
-                    Color::White
-                };
+                    for display_col in cols.clone() {
+                        let col = columns.displayed()[display_col] as i32;
+                        let x = sx + col_xs[display_col] - dx;
+                        let width = col_xs[display_col + 1] - col_xs[display_col];
+                        set_draw_color(theme.grid);
+                        draw::draw_xyline(x, y, x + width - 1);
+                        draw::draw_yxline(x, y, y + height - 1);
 
-                // FIXME could optimize out columns that are not displayed
-                let column_info = model.column_info();
-                for col in 0i32..column_info.details.len() as i32 {
-                    let width = column_info.details[col as usize].width as i32;
-                    draw_frame("LLTT", x, y, width, height);
-
-                    // should we clip?
-                    push_clip(x, y, width - 1, height - 1);
-
-                    match model.get_cell(row, col) {
-                        SimpleCell::Text(cell) => {
-                            draw::set_font(new_font, new_font_size);
-                            draw_rect_fill(x, y, width, height, bg_color);
-                            set_draw_color(Color::Black);
-                            draw_text2(&cell, x, y, width, height, Align::Left);
-                        }
-                        SimpleCell::Delegate(cell) => {
-                            cell.draw(row, col, x, y, width, height, selected);
-                        }
-                        SimpleCell::Widget(mut w) => {
-                            w.set_pos(x, y);
-                            w.set_size(width, height);
-                            table.add(&w);
-                            table.draw_child(&mut w);
-                            table.remove(&w);
+                        // should we clip?
+                        push_clip(x, y, width - 1, height - 1);
+
+                        let style = row_style.merge(model.cell_style(row, col));
+                        let selected = selection.is_selected(row as usize, display_col);
+                        let bg_color = if selected {
+                            theme.selection_background
+                        } else {
+                            style.bg.unwrap_or(theme.row_background(row))
+                        };
+                        match model.get_cell(row, col) {
+                            SimpleCell::Delegate(cell) => {
+                                draw_rect_fill(x, y, width, height, bg_color);
+                                cell.draw(row, col, x, y, width, height, selected);
+                            }
+                            SimpleCell::Widget(mut w) => {
+                                w.set_pos(x, y);
+                                w.set_size(width, height);
+                                table.add(&w);
+                                table.draw_child(&mut w);
+                                table.remove(&w);
+                            }
+                            SimpleCell::None => {
+                                draw_rect_fill(x, y, width, height, bg_color);
+                            }
+                            cell => {
+                                draw::set_font(
+                                    style.font_or(theme.font),
+                                    style.size.unwrap_or(theme.font_size),
+                                );
+                                draw_rect_fill(x, y, width, height, bg_color);
+                                let color = if selected {
+                                    theme.selected_foreground()
+                                } else {
+                                    style.fg.unwrap_or(theme.foreground)
+                                };
+                                let padding = style.padding.unwrap_or(0);
+                                draw_value(
+                                    &cell,
+                                    (
+                                        x + padding,
+                                        y + padding,
+                                        width - 2 * padding,
+                                        height - 2 * padding,
+                                    ),
+                                    style.align,
+                                    color,
+                                    &theme,
+                                );
+                            }
                         }
-                        SimpleCell::None => {
-                            draw_rect_fill(x, y, width, height, bg_color);
+                        pop_clip();
+                        if focused.is_some_and(|c| c.is_at(row, display_col as i32)) {
+                            set_draw_color(theme.focus);
+                            draw::draw_focus_rect(x + 1, y + 1, width - 2, height - 2);
                         }
                     }
-                    pop_clip();
-                    x += width;
                 }
+                pop_clip();
             }
-            header.redraw();
-
-            //sleep(0.01);
+            // editor overlays the cells
+            table.draw_children();
         });
     }
 
+    /// Scrolled rows that are at least partially visible below the frozen rows.
+    fn visible_range(heights: &HeightIndex, scroll: &Scroll, frozen_rows: usize) -> Range<usize> {
+        let top = scroll.yposition();
+        let rows = heights.rows_between(top + heights.row_y(frozen_rows), top + scroll.h());
+        let start = rows.start.max(frozen_rows);
+        start..rows.end.max(start)
+    }
+
+    /// Scroll so a row is at the top, below any frozen rows.
+    pub fn scroll_to_row(&mut self, row: usize) {
+        let frozen_rows = self.frozen.lock().unwrap().rows as i32;
+        let y = {
+            let mut model = self.model.lock().unwrap();
+            self.row_y(&mut model, row.min(i32::MAX as usize) as i32)
+                - self.row_y(&mut model, frozen_rows)
+        };
+        self.scroll_to(self.scroll.xposition(), y.max(0));
+    }
+
+    /// Scrolled rows that are at least partially visible, below any frozen rows.
+    pub fn visible_rows(&self) -> Range<usize> {
+        let frozen_rows = self.frozen.lock().unwrap().rows;
+        let mut model = self.model.lock().unwrap();
+        let heights = Self::heights(&mut model, &self.heights);
+        Self::visible_range(&heights, &self.scroll, frozen_rows)
+    }
+
+    /// Keep the first rows in place while the rest scroll.
+    pub fn freeze_rows(&mut self, rows: usize) {
+        self.frozen.lock().unwrap().rows = rows;
+        self.body.redraw();
+    }
+
+    /// Keep the first displayed columns in place while the rest scroll.
+    pub fn freeze_columns(&mut self, cols: usize) {
+        self.frozen.lock().unwrap().cols = cols;
+        self.header.redraw();
+        self.body.redraw();
+    }
+
+    /// Width of the frozen columns and height of the frozen rows.
+    fn frozen_size(&self) -> (i32, i32) {
+        let frozen = *self.frozen.lock().unwrap();
+        let mut model = self.model.lock().unwrap();
+        let columns = self.columns.lock().unwrap();
+        let cols = frozen.cols.min(columns.displayed().len());
+        let rows = frozen.rows.min(i32::MAX as usize) as i32;
+        (
+            Self::col_x(&mut model, &columns, cols),
+            self.row_y(&mut model, rows),
+        )
+    }
+
+    /// Position in the table's content under window coordinates.  Frozen rows and columns don't scroll.
+    fn content_pos(&self, event_x: i32, event_y: i32) -> (i32, i32) {
+        let (frozen_w, frozen_h) = self.frozen_size();
+        let (x, y) = (event_x - self.scroll.x(), event_y - self.scroll.y());
+        (
+            if x < frozen_w {
+                x
+            } else {
+                event_x - self.table.x()
+            },
+            if y < frozen_h {
+                y
+            } else {
+                event_y - self.table.y()
+            },
+        )
+    }
+
+    /// Window coordinates of a position in the table's content.
+    fn window_pos(&self, x: i32, y: i32) -> (i32, i32) {
+        let (frozen_w, frozen_h) = self.frozen_size();
+        (
+            if x < frozen_w {
+                self.scroll.x() + x
+            } else {
+                self.table.x() + x
+            },
+            if y < frozen_h {
+                self.scroll.y() + y
+            } else {
+                self.table.y() + y
+            },
+        )
+    }
+
+    /// Scroll the content.  Frozen rows and columns must be redrawn, rather than moved with the content.
+    fn scroll_to(&mut self, x: i32, y: i32) {
+        self.scroll.scroll_to(x, y);
+        self.body.redraw();
+        self.header.redraw();
+    }
+
+    /// Row positions, brought up to date with the model.
+    fn heights<'a>(model: &mut T, heights: &'a Mutex<HeightIndex>) -> MutexGuard<'a, HeightIndex> {
+        let mut heights = heights.lock().unwrap();
+        heights.update(&model.row_info());
+        heights
+    }
+
+    /// Export the selected, visible or all rows.
+    pub fn export(
+        &self,
+        rows: ExportRows,
+        exporter: &mut dyn Exporter,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        let count = self.model.lock().unwrap().row_info().count;
+        let columns = self.columns.lock().unwrap().clone();
+        let (rows, cols): (Box<dyn Iterator<Item = usize>>, Vec<usize>) = match rows {
+            ExportRows::All => (Box::new(0..count), columns.displayed().to_vec()),
+            ExportRows::Selection => {
+                let selection = self.selection.lock().unwrap();
+                let cols = selection.selected_cols(columns.displayed().len());
+                let cols = cols
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|c| columns.model_col(c));
+                let rows = selection.selected_rows(count).into_iter().flatten();
+                (Box::new(rows), cols.collect())
+            }
+            ExportRows::Visible => (Box::new(self.visible_rows()), columns.displayed().to_vec()),
+        };
+        export_cells(&mut *self.model.lock().unwrap(), rows, &cols, exporter, out)
+    }
+
+    /// Model columns in display order, including hidden columns.
+    pub fn column_order(&self) -> Vec<usize> {
+        let count = self.model.lock().unwrap().column_info().details.len();
+        let mut columns = self.columns.lock().unwrap();
+        columns.update(count);
+        columns.order().to_vec()
+    }
+
+    /// Display the model columns in this order.  Columns missing from the order are displayed last.
+    pub fn set_column_order(&mut self, order: Vec<usize>) {
+        let count = self.model.lock().unwrap().column_info().details.len();
+        {
+            let mut columns = self.columns.lock().unwrap();
+            columns.update(count);
+            columns.set_order(order);
+        }
+        self.header.redraw();
+        self.body.redraw();
+    }
+
+    /// Capture the user's adjustments, to be restored later with `restore_state`.
+    pub fn save_state(&self) -> TableViewState {
+        let details = self.model.lock().unwrap().column_info().details;
+        let mut state = TableViewState::default();
+        state.save_columns(
+            &self.columns.lock().unwrap(),
+            &details,
+            &self.sort_keys.lock().unwrap().keys,
+        );
+        if let Some(input) = &self.quick_filter {
+            state.filter = input.value();
+        }
+        let count = self.model.lock().unwrap().row_info().count;
+        state.selection = self.selection.lock().unwrap().selected_rows(count);
+        state.top_row = self.visible_rows().start;
+        // the first column scrolled past the frozen columns
+        let left_col = self.col_at(self.scroll.x() + self.frozen_size().0);
+        if let Some(model_col) = left_col.and_then(|c| self.columns.lock().unwrap().model_col(c)) {
+            state.left_col = Some(details[model_col].header.clone());
+        }
+        state
+    }
+
+    /// Apply a saved state.  Columns the model no longer has are ignored, and new columns are displayed last.
+    pub fn restore_state(&mut self, state: &TableViewState) {
+        let details = self.model.lock().unwrap().column_info().details;
+        let keys = state.restore_columns(&mut self.columns.lock().unwrap(), &details);
+        self.set_sort_keys(keys);
+        if let Some(input) = &mut self.quick_filter {
+            input.set_value(&state.filter);
+            input.do_callback();
+        }
+        self.selection
+            .lock()
+            .unwrap()
+            .select_row_ranges(&state.selection);
+        self.selection_changed();
+        let (frozen_w, frozen_h) = self.frozen_size();
+        let (x, y) = {
+            let mut model = self.model.lock().unwrap();
+            let columns = self.columns.lock().unwrap();
+            let left_col = state
+                .left_col(&details)
+                .and_then(|c| columns.display_col(c));
+            let row = state.top_row.min(model.row_info().count) as i32;
+            (
+                Self::col_x(&mut model, &columns, left_col.unwrap_or(0)) - frozen_w,
+                self.row_y(&mut model, row) - frozen_h,
+            )
+        };
+        self.scroll_to(x.max(0), y.max(0));
+        self.resized();
+    }
+
+    /// Open the cell editor, if the cell is editable.  The column is a model column.
+    pub fn edit_cell(&mut self, row: i32, col: i32) -> bool {
+        let (x, y, w, h) = {
+            let mut model = self.model.lock().unwrap();
+            if !model.is_editable(row, col) {
+                return false;
+            }
+            let columns = self.columns.lock().unwrap();
+            let display_col = match columns.display_col(col as usize) {
+                Some(c) => c,
+                None => return false,
+            };
+            (
+                Self::col_x(&mut model, &columns, display_col),
+                self.row_y(&mut model, row),
+                Self::col_width(&mut model, &columns, col),
+                self.row_height(&mut model, row),
+            )
+        };
+        let (x, y) = self.window_pos(x, y);
+        let rect = (x, y, w, h);
+        let mut table = self.table.clone();
+        CellEditor::open(
+            &self.editor,
+            &mut self.table,
+            self.model.clone(),
+            row,
+            col,
+            rect,
+            move || {
+                table.redraw();
+                let _ = table.take_focus();
+            },
+        );
+        true
+    }
+
+    /// Include the column headers when copying to the clipboard.
+    pub fn set_copy_headers(&mut self, headers: bool) {
+        *self.copy_headers.lock().unwrap() = headers;
+    }
+
+    /// Copy the selected rows to the clipboard as tab separated values.
+    pub fn copy_selection(&self) {
+        let mut out = Vec::new();
+        let mut exporter = TsvExporter {
+            header: *self.copy_headers.lock().unwrap(),
+        };
+        // writing to a Vec can't fail
+        let _ = self.export(ExportRows::Selection, &mut exporter, &mut out);
+        app::copy(&String::from_utf8_lossy(&out));
+    }
+
+    /// Paste tab separated values, starting at the top left of the selection.
+    pub fn paste(&mut self, text: &str) {
+        let Some((row, col)) = self.selection.lock().unwrap().top_left() else {
+            return;
+        };
+        let result = self.columns.lock().unwrap().paste(
+            &mut *self.model.lock().unwrap(),
+            row as i32,
+            col,
+            text,
+        );
+        if let Err(e) = result {
+            dialog::alert_default(&e);
+        }
+        self.body.redraw();
+    }
+
     fn init_header(&mut self) {
         let model = self.model.clone();
-        let table = self.table.clone();
-        self.header.set_size(self.width_total(), 20);
-        let new_font = self.font;
-        let new_font_size = self.font_size;
+        let scroll = self.scroll.clone();
+        self.header.set_size(self.scroll.width(), 20);
+        let theme = self.theme.clone();
+        let sort_keys = self.sort_keys.clone();
+        let columns = self.columns.clone();
+        let frozen = self.frozen.clone();
         self.header.draw(move |frame| {
+            let theme = *theme.lock().unwrap();
             let mut model = model.lock().unwrap();
+            let sort_keys = sort_keys.lock().unwrap();
+            let mut columns = columns.lock().unwrap();
             let height = frame.height();
-            let mut x = table.x();
             let y = frame.y();
-            for col in model.column_info().details {
-                let width = col.width as i32;
-                draw_rect_fill(x, y, width, height, Color::White);
-                draw_frame("AADD", x, y, width, height);
-                // should we clip?
-                set_draw_color(Color::Black);
-                let cell = col.header.clone();
-                let font = font();
-                draw::set_font(new_font, new_font_size);
-                draw_text2(&cell, x, y, width, height, Align::Left);
-                set_font(font);
-                x += width;
+            let details = model.column_info().details;
+            columns.update(details.len());
+            let frozen_cols = frozen.lock().unwrap().cols.min(columns.displayed().len());
+            let frozen_w: i32 = columns.displayed()[..frozen_cols]
+                .iter()
+                .map(|c| columns.width(*c, details[*c].width) as i32)
+                .sum();
+            let (view_w, _) = view_size(&scroll);
+            // corner above the row header
+            if scroll.x() > frame.x() {
+                let w = scroll.x() - frame.x();
+                draw_rect_fill(frame.x(), y, w, height, theme.header_background);
+                set_draw_color(theme.grid);
+                draw::draw_rect(frame.x(), y, w, height);
+            }
+            // scrolled headers beside the frozen ones, then the frozen headers
+            let regions = [
+                (
+                    frozen_cols..,
+                    scroll.xposition(),
+                    frozen_w,
+                    view_w - frozen_w,
+                ),
+                (0.., 0, 0, frozen_w),
+            ];
+            for (cols, dx, clip_x, clip_w) in regions {
+                if clip_w <= 0 {
+                    continue;
+                }
+                push_clip(scroll.x() + clip_x, y, clip_w, height);
+                let mut x = scroll.x() - dx;
+                for (display_col, index) in columns.displayed().iter().copied().enumerate() {
+                    let col = &details[index];
+                    let width = columns.width(index, col.width) as i32;
+                    if cols.contains(&display_col) {
+                        Self::draw_header(&theme, &sort_keys, col, index, (x, y, width, height));
+                    }
+                    x += width;
+                }
+                pop_clip();
             }
         });
+        let mut this = self.clone();
+        // header being dragged, and whether it has moved
+        let mut drag_col: Option<(usize, bool)> = None;
+        // column being resized, with the starting x and width
+        let mut resizing: Option<(usize, i32, i32)> = None;
+        // column whose tooltip is showing
+        let mut tip_col: Option<usize> = None;
+        self.header.handle(move |_header, e| match e {
+            Event::Enter => true,
+            Event::Push if app::event_mouse_button() == MouseButton::Right => {
+                match this.col_at(app::event_x()) {
+                    Some(col) => {
+                        this.header_menu(col);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Event::Move => {
+                let cursor = match this.border_at(app::event_x()) {
+                    Some(_) => Cursor::WE,
+                    None => Cursor::Default,
+                };
+                draw::set_cursor(cursor);
+                let col = this.col_at(app::event_x());
+                if col != tip_col {
+                    tip_col = col;
+                    this.header_tooltip(col);
+                }
+                true
+            }
+            Event::Leave => {
+                draw::set_cursor(Cursor::Default);
+                tip_col = None;
+                true
+            }
+            Event::Push if this.border_at(app::event_x()).is_some() => {
+                let col = this.border_at(app::event_x()).unwrap();
+                let Some(model_col) = this.columns.lock().unwrap().model_col(col) else {
+                    return false;
+                };
+                if app::event_clicks() {
+                    this.autofit_column(model_col);
+                } else {
+                    let width = this.column_width(model_col);
+                    resizing = Some((model_col, app::event_x(), width));
+                }
+                true
+            }
+            Event::Drag if resizing.is_some() => {
+                let (col, start_x, width) = resizing.unwrap();
+                this.set_column_width(col, width + app::event_x() - start_x);
+                true
+            }
+            Event::Released if resizing.is_some() => {
+                resizing = None;
+                true
+            }
+            Event::Push => match this.col_at(app::event_x()) {
+                Some(col) => {
+                    drag_col = Some((col, false));
+                    true
+                }
+                None => false,
+            },
+            Event::Drag => match drag_col.as_mut() {
+                Some((_, moved)) => {
+                    *moved = true;
+                    true
+                }
+                None => false,
+            },
+            Event::Released => match drag_col.take() {
+                Some((from, true)) => {
+                    if let Some(to) = this.col_at(app::event_x()) {
+                        if to != from {
+                            this.columns.lock().unwrap().move_col(from, to);
+                            this.header.redraw();
+                            this.body.redraw();
+                        }
+                    }
+                    true
+                }
+                Some((col, false)) => {
+                    let Some(col) = this.columns.lock().unwrap().model_col(col) else {
+                        return false;
+                    };
+                    let extend = app::event_state().contains(EventState::Shift);
+                    let keys = {
+                        let mut sort_keys = this.sort_keys.lock().unwrap();
+                        sort_keys.click(col, extend);
+                        sort_keys.keys.clone()
+                    };
+                    this.set_sort_keys(keys);
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        });
+    }
+
+    fn init_row_header(&mut self) {
+        let model = self.model.clone();
+        let scroll = self.scroll.clone();
+        let theme = self.theme.clone();
+        let heights = self.heights.clone();
+        let frozen = self.frozen.clone();
+        let selection = self.selection.clone();
+        self.row_header.draw(move |frame| {
+            let theme = *theme.lock().unwrap();
+            let mut model = model.lock().unwrap();
+            let heights = Self::heights(&mut model, &heights);
+            let selection = selection.lock().unwrap();
+            let frozen_rows = frozen.lock().unwrap().rows.min(heights.count());
+            let frozen_h = heights.row_y(frozen_rows);
+            let (_, view_h) = view_size(&scroll);
+            let (x, w) = (frame.x(), frame.w());
+            draw_rect_fill(x, frame.y(), w, frame.h(), theme.header_background);
+            let (font, size) = (draw::font(), draw::size());
+            draw::set_font(theme.header_font, theme.header_font_size);
+            // scrolled rows below the frozen ones, then the frozen rows
+            let regions = [
+                (
+                    Self::visible_range(&heights, &scroll, frozen_rows),
+                    scroll.yposition(),
+                    frozen_h,
+                    view_h - frozen_h,
+                ),
+                (0..frozen_rows, 0, 0, frozen_h),
+            ];
+            for (rows, dy, clip_y, clip_h) in regions {
+                if clip_h <= 0 {
+                    continue;
+                }
+                push_clip(x, scroll.y() + clip_y, w, clip_h);
+                for row in rows {
+                    let y = scroll.y() + heights.row_y(row) - dy;
+                    let height = heights.row_height(row);
+                    let (bg, fg) = if selection.is_row_selected(row) {
+                        (theme.selection_background, theme.selected_foreground())
+                    } else {
+                        (theme.header_background, theme.header_foreground)
+                    };
+                    draw_rect_fill(x, y, w, height, bg);
+                    set_draw_color(theme.grid);
+                    draw::draw_rect(x, y, w, height);
+                    if let Some(label) = model.row_header(row as i32) {
+                        set_draw_color(fg);
+                        draw_text2(&label, x, y, w - 4, height, Align::Right | Align::Clip);
+                    }
+                }
+                pop_clip();
+            }
+            draw::set_font(font, size);
+        });
+        let mut this = self.clone();
+        self.row_header.handle(move |_row_header, e| match e {
+            // select the row, or drag or Shift click to select several
+            Event::Push | Event::Drag => match this.row_at(app::event_y()) {
+                Some(row) => {
+                    let shift = e == Event::Drag || app::event_state().contains(EventState::Shift);
+                    {
+                        let mut cursor = this.cursor.lock().unwrap();
+                        let col = cursor.col;
+                        cursor.set(row as i32, col);
+                    }
+                    this.selection.lock().unwrap().click_row(row, shift);
+                    this.selection_changed();
+                    let _ = this.table.take_focus();
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        });
     }
-    fn width_total(&self) -> i32 {
+
+    /// Show a row header beside the rows, labeled by `SimpleModel::row_header`.  Clicking a row header
+    /// selects the row.
+    pub fn set_row_header(&mut self, show: bool) {
+        match show {
+            true => self.row_header.show(),
+            false => self.row_header.hide(),
+        }
+        self.pack.redraw();
+    }
+
+    pub fn set_row_header_width(&mut self, width: i32) {
+        let height = self.row_header.h();
+        self.row_header.set_size(width, height);
+        self.pack.redraw();
+    }
+
+    fn draw_header(
+        theme: &TableTheme,
+        sort_keys: &SortKeys,
+        col: &ColumnDetail,
+        index: usize,
+        (x, y, width, height): (i32, i32, i32, i32),
+    ) {
+        draw_rect_fill(x, y, width, height, theme.header_background);
+        set_draw_color(theme.grid);
+        draw::draw_rect(x, y, width, height);
+        set_draw_color(theme.header_foreground);
+        let (font, size) = (draw::font(), draw::size());
+        draw::set_font(theme.header_font, theme.header_font_size);
+        let text_width = header_text_width(sort_keys, index, width);
+        let text = ellipsize(&col.header, text_width);
+        push_clip(x, y, width, height);
+        draw_text2(
+            text.as_deref().unwrap_or(&col.header),
+            x + 2,
+            y,
+            text_width,
+            height,
+            Align::Left | Align::Clip,
+        );
+        pop_clip();
+        draw::set_font(font, size);
+        let color = theme.header_foreground;
+        draw_sort_indicator(sort_keys, index, (x, y, width, height), color);
+    }
+
+    /// Show the whole header text of a displayed column when it is cut short, or hide the tooltip.
+    fn header_tooltip(&self, col: Option<usize>) {
+        let tip = col.and_then(|col| {
+            let details = self.model.lock().unwrap().column_info().details;
+            let columns = self.columns.lock().unwrap();
+            let index = columns.model_col(col)?;
+            let detail = details.get(index)?;
+            let width = columns.width(index, detail.width) as i32;
+            let theme = *self.theme.lock().unwrap();
+            let (font, size) = (draw::font(), draw::size());
+            draw::set_font(theme.header_font, theme.header_font_size);
+            let text_width = header_text_width(&self.sort_keys.lock().unwrap(), index, width);
+            let truncated = ellipsize(&detail.header, text_width).is_some();
+            draw::set_font(font, size);
+            truncated.then(|| static_text(&detail.header))
+        });
+        let header = &self.header;
+        // an empty tip hides the tooltip
+        Tooltip::enter_area(header, 0, 0, header.w(), header.h(), tip.unwrap_or(c""));
+    }
+
+    /// Current sort keys, highest priority first.
+    pub fn sort_keys(&self) -> Vec<(usize, Order)> {
+        self.sort_keys.lock().unwrap().keys.clone()
+    }
+
+    /// Sort the model, and show the keys in the header.
+    pub fn set_sort_keys(&mut self, keys: Vec<(usize, Order)>) {
+        self.follow_rows(Some(&keys));
+        SelectionModel::notify(&self.selection);
+        self.sort_keys.lock().unwrap().keys = keys;
+        self.header.redraw();
+        self.body.redraw();
+    }
+
+    /// Displayed column under the window x coordinate.
+    fn col_at(&self, event_x: i32) -> Option<usize> {
+        let event_x = self.content_pos(event_x, 0).0;
+        if event_x < 0 {
+            // above the row header
+            return None;
+        }
+        let mut x = 0;
+        let details = self.model.lock().unwrap().column_info().details;
+        let columns = self.columns.lock().unwrap();
+        for (col, model_col) in columns.displayed().iter().enumerate() {
+            x += columns.width(*model_col, details[*model_col].width) as i32;
+            if event_x < x {
+                return Some(col);
+            }
+        }
+        None
+    }
+    /// Displayed column whose right border is under the window x coordinate.
+    fn border_at(&self, event_x: i32) -> Option<usize> {
+        let event_x = self.content_pos(event_x, 0).0;
+        let mut x = 0;
+        let details = self.model.lock().unwrap().column_info().details;
+        let columns = self.columns.lock().unwrap();
+        for (col, model_col) in columns.displayed().iter().enumerate() {
+            x += columns.width(*model_col, details[*model_col].width) as i32;
+            if (event_x - x).abs() <= RESIZE_MARGIN {
+                return Some(col);
+            }
+        }
+        None
+    }
+
+    /// Current width of a model column.
+    pub fn column_width(&self, col: usize) -> i32 {
         let mut model = self.model.lock().unwrap();
-        model.column_info().total_width() as i32
+        Self::col_width(&mut model, &self.columns.lock().unwrap(), col as i32)
+    }
+
+    /// Override the model's width for a column.
+    pub fn set_column_width(&mut self, col: usize, width: i32) {
+        self.columns
+            .lock()
+            .unwrap()
+            .set_width(col, width.max(0) as u32);
+        self.resized();
+    }
+
+    /// Fit a column to its header and the visible rows.
+    pub fn autofit_column(&mut self, col: usize) {
+        let rows = self.visible_rows();
+        let (font, size) = (draw::font(), draw::size());
+        let theme = self.theme();
+        draw::set_font(theme.font, theme.font_size);
+        let width = fit_width(&mut *self.model.lock().unwrap(), col, rows);
+        draw::set_font(font, size);
+        self.columns.lock().unwrap().set_width(col, width);
+        self.resized();
+    }
+
+    /// Go back to the model's column widths.
+    pub fn reset_column_widths(&mut self) {
+        self.columns.lock().unwrap().clear_widths();
+        self.resized();
+    }
+
+    pub fn is_column_hidden(&self, col: usize) -> bool {
+        self.columns.lock().unwrap().is_hidden(col)
+    }
+
+    /// Hide or show a model column.  The last displayed column can't be hidden.
+    pub fn set_column_hidden(&mut self, col: usize, hidden: bool) {
+        self.columns.lock().unwrap().set_hidden(col, hidden);
+        self.resized();
     }
 
+    /// Show every column in model order, at the model's widths.
+    pub fn reset_columns(&mut self) {
+        self.columns.lock().unwrap().reset();
+        self.resized();
+    }
+
+    /// Show the header context menu for a displayed column, and apply the chosen action.
+    fn header_menu(&mut self, col: usize) {
+        let details = self.model.lock().unwrap().column_info().details;
+        let view = self.columns.lock().unwrap().clone();
+        let Some(model_col) = view.model_col(col) else {
+            return;
+        };
+        match header_menu(&details, &view) {
+            Some(HeaderAction::Sort(order)) => self.set_sort_keys(vec![(model_col, order)]),
+            Some(HeaderAction::Autofit) => self.autofit_column(model_col),
+            Some(HeaderAction::Reset) => self.reset_columns(),
+            Some(HeaderAction::Toggle(c)) => {
+                let hidden = self.is_column_hidden(c);
+                self.set_column_hidden(c, !hidden);
+            }
+            None => {}
+        }
+    }
+
+    fn resized(&mut self) {
+        let width = self.scroll.width();
+        let height = self.header.height();
+        self.header.set_size(width, height);
+        self.header.redraw();
+        self.body.redraw();
+    }
+
+    /// Row under the window y coordinate.
+    fn row_at(&self, event_y: i32) -> Option<usize> {
+        let y = self.content_pos(self.scroll.x(), event_y).1;
+        let mut model = self.model.lock().unwrap();
+        let heights = Self::heights(&mut model, &self.heights);
+        let row = heights.row_at(y);
+        (row < heights.count()).then_some(row)
+    }
+
+    /// Row and model column at the window coordinates.
     fn pos_to_row_col(&self, event_x: i32, event_y: i32) -> Option<(i32, i32)> {
-        let x = event_x - self.table.x();
-        let y = event_y - self.table.y();
+        let (x, y) = self.content_pos(event_x, event_y);
         let model = &mut self.model.lock().unwrap();
+        let columns = self.columns.lock().unwrap();
 
-        let row = bin_search(model.row_info().count, &mut |row: usize| {
-            let row = row as i32;
-            let row_y = Self::row_y(model, row);
-            let row_y2 = row_y + Self::row_height(model, row);
-            if y.cmp(&row_y) == Ordering::Less {
-                Ordering::Less
-            } else if y.cmp(&row_y2) == Ordering::Greater {
-                Ordering::Greater
-            } else {
-                Ordering::Equal
-            }
-        });
+        let row = {
+            let heights = Self::heights(model, &self.heights);
+            // below the last row is the last row
+            heights.row_at(y).min(heights.count().checked_sub(1)?)
+        };
 
-        let column = bin_search(model.column_info().details.len(), &mut |col| {
-            let col = col as i32;
-            let col_x = Self::col_x(model, col);
-            let col_x2 = col_x + Self::col_width(model, col);
-            if x.cmp(&col_x) == Ordering::Less {
-                Ordering::Less
-            } else if x.cmp(&col_x2) == Ordering::Greater {
-                Ordering::Greater
-            } else {
-                Ordering::Equal
-            }
-        });
-        Some((row as i32, column as i32))
+        // right edge of each displayed column
+        let details = model.column_info().details;
+        let ends: Vec<i32> = columns
+            .displayed()
+            .iter()
+            .scan(0, |end, c| {
+                *end += columns.width(*c, details[*c].width) as i32;
+                Some(*end)
+            })
+            .collect();
+        // left of the first column is the first column, right of the last is the last
+        let column = ends
+            .partition_point(|end| *end <= x)
+            .min(ends.len().checked_sub(1)?);
+        Some((row as i32, columns.model_col(column)? as i32))
     }
 
-    fn col_width(model: &mut std::sync::MutexGuard<'_, T>, col: i32) -> i32 {
-        model.column_info().details[col as usize].width as i32
+    fn col_width(model: &mut std::sync::MutexGuard<'_, T>, columns: &ColumnView, col: i32) -> i32 {
+        let col = col as usize;
+        columns.width(col, model.column_info().details[col].width) as i32
     }
 
-    fn col_x(model: &mut std::sync::MutexGuard<'_, T>, col: i32) -> i32 {
-        model
-            .column_info()
-            .details
+    /// Offset of a displayed column.
+    fn col_x(
+        model: &mut std::sync::MutexGuard<'_, T>,
+        columns: &ColumnView,
+        display_col: usize,
+    ) -> i32 {
+        let details = model.column_info().details;
+        columns
+            .displayed()
             .iter()
-            .take(col as usize)
-            .map(|c| c.width as i32)
+            .take(display_col)
+            .map(|c| columns.width(*c, details[*c].width) as i32)
             .sum()
     }
 
-    fn row_height(model: &mut std::sync::MutexGuard<'_, T>, row: i32) -> i32 {
-        model.row_info().height.for_row(row as u32) as i32
+    fn row_height(&self, model: &mut T, row: i32) -> i32 {
+        Self::heights(model, &self.heights).row_height(row.max(0) as usize)
+    }
+
+    fn row_y(&self, model: &mut T, row: i32) -> i32 {
+        Self::heights(model, &self.heights).row_y(row.max(0) as usize)
+    }
+
+    /// Move the focused cell for an arrow, page, Home or End key, selecting rows and scrolling to show it.
+    fn navigate(&mut self, key: Key) -> bool {
+        let (rows, cols, page) = {
+            let mut model = self.model.lock().unwrap();
+            let rows = model.row_info().count as i32;
+            let cols = self.columns.lock().unwrap().displayed().len() as i32;
+            let row = self.cursor.lock().unwrap().row.clamp(0, (rows - 1).max(0));
+            let page = self.scroll.h() / self.row_height(&mut model, row).max(1);
+            (rows, cols, page)
+        };
+        let cursor = {
+            let mut cursor = self.cursor.lock().unwrap();
+            if !cursor.navigate(key, rows, cols, page) {
+                return false;
+            }
+            *cursor
+        };
+        let (row, col) = (cursor.row as usize, cursor.col as usize);
+        if app::event_state().contains(EventState::Shift) {
+            self.selection.lock().unwrap().extend_to(row, col);
+        } else {
+            self.selection.lock().unwrap().click(row, col, false, false);
+        }
+        self.selection_changed();
+        self.show_cell(cursor.row, cursor.col as usize);
+        true
+    }
+
+    /// Sort, or catch up with changes to the model, keeping the selection, cursor and top row on the same rows
+    /// when the model has row keys.  Callers notify selection listeners, as this also runs on timer threads.
+    fn follow_rows(&mut self, sort: Option<&[(usize, Order)]>) {
+        let top = self.visible_rows().start;
+        let new_y = {
+            let mut model = self.model.lock().unwrap();
+            let mut selection = self.selection.lock().unwrap();
+            let mut cursor = self.cursor.lock().unwrap();
+            // part of the top row scrolled out of view
+            let offset = self.scroll.yposition() - self.row_y(&mut model, top as i32);
+            if let Some(keys) = sort {
+                selection.remember_rows(&mut *model, &cursor, top);
+                model.sort_multi(keys);
+                self.heights.lock().unwrap().clear();
+            }
+            selection
+                .follow_rows(&mut *model, &mut cursor, top)
+                .map(|top| self.row_y(&mut model, top as i32) + offset)
+        };
+        if let Some(y) = new_y {
+            self.scroll_to(self.scroll.xposition(), y);
+        }
+    }
+
+    /// Tell the listeners, and show the new selection.
+    fn selection_changed(&mut self) {
+        SelectionModel::notify(&self.selection);
+        self.body.redraw();
     }
 
-    fn row_y(model: &mut std::sync::MutexGuard<'_, T>, row: i32) -> i32 {
-        model.row_info().height.for_range(0..row as u32) as i32
+    /// Scroll so a row and displayed column are visible.
+    fn show_cell(&mut self, row: i32, display_col: usize) {
+        let (x, y, w, h) = {
+            let mut model = self.model.lock().unwrap();
+            let columns = self.columns.lock().unwrap();
+            let Some(col) = columns.model_col(display_col) else {
+                return;
+            };
+            let col = col as i32;
+            (
+                Self::col_x(&mut model, &columns, display_col),
+                self.row_y(&mut model, row),
+                Self::col_width(&mut model, &columns, col),
+                self.row_height(&mut model, row),
+            )
+        };
+        let (view_w, view_h) = view_size(&self.scroll);
+        let (frozen_w, frozen_h) = self.frozen_size();
+        let (xpos, ypos) = (self.scroll.xposition(), self.scroll.yposition());
+        // frozen cells are always shown, and the rest scroll in the view beside the frozen cells
+        let new_x = match x < frozen_w {
+            true => xpos,
+            false => scroll_into(xpos + frozen_w, view_w - frozen_w, x, w) - frozen_w,
+        };
+        let new_y = match y < frozen_h {
+            true => ypos,
+            false => scroll_into(ypos + frozen_h, view_h - frozen_h, y, h) - frozen_h,
+        };
+        if (new_x, new_y) != (xpos, ypos) {
+            self.scroll_to(new_x, new_y);
+        }
+        self.body.redraw();
+    }
+
+    /// Run the action of a displayed cell.  Widgets are clicked, and cells without an action are edited.
+    fn activate(&mut self, row: i32, display_col: usize) -> bool {
+        let Some(col) = self.columns.lock().unwrap().model_col(display_col) else {
+            return false;
+        };
+        let col = col as i32;
+        let mut model = self.model.lock().unwrap();
+        if row < 0 || row >= model.row_info().count as i32 {
+            return false;
+        }
+        if let SimpleCell::Widget(mut w) = model.get_cell(row, col) {
+            w.do_callback();
+            return true;
+        }
+        if model.activate(row, col) {
+            drop(model);
+            // rows below may have been shown or hidden, as when a tree row expands
+            self.rows_changed(row);
+            return true;
+        }
+        drop(model);
+        self.edit_cell(row, col)
+    }
+
+    /// Expand or collapse a tree row.
+    fn expand(&mut self, row: i32, expand: bool) -> bool {
+        let expanded = self.model.lock().unwrap().expand(row, expand);
+        if expanded {
+            self.rows_changed(row);
+        }
+        expanded
+    }
+
+    /// Let the model handle a click on a cell, as when collapsing a group.
+    fn click(&mut self, row: i32, col: i32) -> bool {
+        let clicked = self.model.lock().unwrap().click(row, col);
+        if clicked {
+            self.rows_changed(row);
+        }
+        clicked
+    }
+
+    /// Rows below a row were shown or hidden.
+    fn rows_changed(&mut self, row: i32) {
+        self.heights.lock().unwrap().forget(row.max(0) as usize);
+        self.body.redraw();
     }
 
-    pub fn select_rows(&mut self, selection: Range<usize>) {
-        *(self.selection.lock().unwrap()) = selection;
+    pub fn select_rows(&mut self, rows: Range<usize>) {
+        self.selection.lock().unwrap().select_rows(rows);
+        self.selection_changed();
     }
 
     pub fn select_row(&mut self, row: usize) {
         self.select_rows(row..row + 1);
     }
 
+    /// First to last selected row.
     pub fn get_selection(&self) -> Range<usize> {
-        self.selection.lock().unwrap().clone()
+        let count = self.model.lock().unwrap().row_info().count;
+        let rows = self.selection.lock().unwrap().selected_rows(count);
+        match (rows.first(), rows.last()) {
+            (Some(first), Some(last)) => first.start..last.end,
+            _ => 0..0,
+        }
+    }
+
+    /// Whether clicks select rows, cells or columns.  Clears the selection.
+    pub fn set_selection_mode(&mut self, mode: SelectionMode) {
+        self.selection.lock().unwrap().set_mode(mode);
+        self.selection_changed();
+    }
+
+    /// Called with the new selection whenever the user changes it.
+    pub fn on_selection_changed(&mut self, listener: impl FnMut(&SelectionModel) + Send + 'static) {
+        self.selection
+            .lock()
+            .unwrap()
+            .on_selection_changed(listener);
     }
 }
 
-// replace with library fn when found.  The only known binary search is on slices, which would force us to have an allocation for every row.
-fn bin_search(size: usize, measure_fn: &mut impl FnMut(usize) -> Ordering) -> usize {
-    let mut left = 0;
-    let mut right = size - 1;
-    let mut m = 0;
-    while left <= right {
-        m = left + (right - left) / 2;
-        match measure_fn(m) {
-            Ordering::Greater => left = m + 1,
-            Ordering::Equal => return m,
-            Ordering::Less => right = m - 1,
-        }
+impl<M> JoeTable<FilteredModel<M>>
+where
+    M: SimpleModel + 'static,
+{
+    /// Filter the rows as text is typed into the input.
+    pub fn set_quick_filter(&mut self, input: &mut Input) {
+        self.quick_filter = Some(input.clone());
+        let model = self.model.clone();
+        let mut this = self.clone();
+        input.set_trigger(CallbackTrigger::Changed);
+        input.set_callback(move |input| {
+            model.lock().unwrap().set_text(&input.value());
+            this.heights.lock().unwrap().clear();
+            // previously selected rows are no longer valid
+            this.select_rows(0..0);
+            *this.cursor.lock().unwrap() = Default::default();
+            this.scroll_to(0, 0);
+            this.body.redraw();
+        });
     }
-    m
 }
 
-fn bin_find(size: usize, measure_fn: &mut impl FnMut(usize) -> Ordering) -> Option<usize> {
-    let m = bin_search(size, measure_fn);
-    if measure_fn(m) == Ordering::Equal {
-        Some(m)
+/// Size of a scroll's view, without its scrollbars.
+fn view_size(scroll: &Scroll) -> (i32, i32) {
+    let vbar = scroll.scrollbar();
+    let hbar = scroll.hscrollbar();
+    (
+        scroll.w() - if vbar.visible() { vbar.w() } else { 0 },
+        scroll.h() - if hbar.visible() { hbar.h() } else { 0 },
+    )
+}
+
+/// Scroll position that shows `len` pixels from `start` in a view of `view` pixels, moving as little as possible.
+fn scroll_into(pos: i32, view: i32, start: i32, len: i32) -> i32 {
+    if start < pos {
+        start
+    } else if start + len > pos + view {
+        (start + len - view).min(start)
     } else {
-        None
+        pos
     }
 }
@@ -1,4 +1,18 @@
+mod cell_draw;
+pub mod cell_editor;
+mod cursor;
+pub mod column_view;
+pub mod filtered_model;
+pub mod grouped_model;
+mod header;
+mod height_index;
+pub mod headless;
 pub mod joe_table;
+pub mod model_events;
 pub mod simple_model;
+pub mod selection;
 pub mod simple_table;
 pub mod spark_line;
+pub mod theme;
+pub mod tree_model;
+pub mod view_state;
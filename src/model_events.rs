@@ -0,0 +1,101 @@
+use std::{
+    mem,
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+use fltk::app;
+
+/// A change to a model's data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelEvent {
+    /// Rows were inserted, at their new positions.
+    RowsInserted(Range<usize>),
+    /// Rows were removed, at their old positions.
+    RowsRemoved(Range<usize>),
+    /// Cells of the rows changed.
+    RowsUpdated(Range<usize>),
+    /// Columns were added, removed or renamed.
+    ColumnsChanged,
+    /// Anything may have changed.
+    Reset,
+}
+
+impl ModelEvent {
+    /// True if only cells changed, so rows keep their positions.
+    pub fn is_update(&self) -> bool {
+        matches!(self, ModelEvent::RowsUpdated(_))
+    }
+}
+
+type Listener = Box<dyn FnMut(&ModelEvent) + Send>;
+
+/// Reports a model's changes to the tables showing it, instead of tables polling with a timer.
+/// Clones share the listeners, so a model can return a clone from `SimpleModel::events` and keep
+/// another wherever its data is updated, on any thread.
+#[derive(Clone, Default)]
+pub struct ModelEvents {
+    listeners: Arc<Mutex<Vec<Listener>>>,
+}
+
+impl ModelEvents {
+    pub fn new() -> ModelEvents {
+        Default::default()
+    }
+
+    /// Called for each event, on the thread sending it.  Listeners must not lock the model, which
+    /// may be locked by the sender.
+    pub fn subscribe(&self, listener: impl FnMut(&ModelEvent) + Send + 'static) {
+        self.listeners.lock().unwrap().push(Box::new(listener));
+    }
+
+    pub fn send(&self, event: ModelEvent) {
+        for listener in self.listeners.lock().unwrap().iter_mut() {
+            listener(&event);
+        }
+    }
+
+    pub fn rows_inserted(&self, rows: Range<usize>) {
+        self.send(ModelEvent::RowsInserted(rows));
+    }
+
+    pub fn rows_removed(&self, rows: Range<usize>) {
+        self.send(ModelEvent::RowsRemoved(rows));
+    }
+
+    pub fn rows_updated(&self, rows: Range<usize>) {
+        self.send(ModelEvent::RowsUpdated(rows));
+    }
+
+    pub fn columns_changed(&self) {
+        self.send(ModelEvent::ColumnsChanged);
+    }
+
+    pub fn reset(&self) {
+        self.send(ModelEvent::Reset);
+    }
+}
+
+/// Collect events from any thread, and handle them together on the UI thread.
+pub(crate) fn subscribe_ui(
+    events: &ModelEvents,
+    handler: impl FnMut(Vec<ModelEvent>) + Send + 'static,
+) {
+    let pending: Arc<Mutex<Vec<ModelEvent>>> = Default::default();
+    let handler = Arc::new(Mutex::new(handler));
+    events.subscribe(move |event| {
+        let mut queue = pending.lock().unwrap();
+        queue.push(event.clone());
+        if queue.len() == 1 {
+            // nothing is waiting to handle the queue
+            let pending = pending.clone();
+            let handler = handler.clone();
+            app::awake_callback(move || {
+                let events = mem::take(&mut *pending.lock().unwrap());
+                if !events.is_empty() {
+                    (handler.lock().unwrap())(events);
+                }
+            });
+        }
+    });
+}
@@ -0,0 +1,682 @@
+use std::{
+    collections::HashMap,
+    fmt, mem,
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+use fltk::app;
+
+use crate::{cursor::CellCursor, simple_model::SimpleModel};
+
+/// What a click selects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionMode {
+    #[default]
+    Row,
+    Cell,
+    Column,
+}
+
+type Listener = Box<dyn FnMut(&SelectionModel) + Send>;
+
+/// Selected rows, cells or columns, shared by a table and anything following its selection.
+/// The selection is a set of (rows, columns) ranges, so Ctrl+click can build disjoint selections.
+/// Columns are displayed columns.
+#[derive(Default)]
+pub struct SelectionModel {
+    mode: SelectionMode,
+    /// selected areas as (rows, columns).  Row mode selects every column, column mode every row.
+    ranges: Vec<(Range<usize>, Range<usize>)>,
+    /// where Shift+click extends from
+    anchor: Option<(usize, usize)>,
+    /// true if the last range extends from the anchor
+    extending: bool,
+    changed: bool,
+    /// counts changes, to tell if remembered rows are still the selection
+    generation: u64,
+    /// rows remembered by key, when the model has keys
+    keys: Option<RowKeys>,
+    listeners: Vec<Listener>,
+}
+
+/// Selected rows, focused cell and top row, by `SimpleModel::row_key`.
+#[derive(PartialEq)]
+struct RowKeys {
+    generation: u64,
+    count: usize,
+    /// keys of the rows of each range, or None for ranges of every row
+    ranges: Vec<(Option<Vec<u64>>, Range<usize>)>,
+    anchor: Option<(u64, usize)>,
+    cursor: Option<(i32, u64)>,
+    top: Option<(usize, u64)>,
+}
+
+impl Clone for SelectionModel {
+    /// Copies the selection, without the listeners.
+    fn clone(&self) -> Self {
+        SelectionModel {
+            mode: self.mode,
+            ranges: self.ranges.clone(),
+            anchor: self.anchor,
+            extending: self.extending,
+            changed: false,
+            generation: 0,
+            keys: None,
+            listeners: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Debug for SelectionModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SelectionModel")
+            .field("mode", &self.mode)
+            .field("ranges", &self.ranges)
+            .field("anchor", &self.anchor)
+            .finish()
+    }
+}
+
+impl SelectionModel {
+    pub fn new(mode: SelectionMode) -> SelectionModel {
+        SelectionModel {
+            mode,
+            ..Default::default()
+        }
+    }
+
+    pub fn mode(&self) -> SelectionMode {
+        self.mode
+    }
+
+    /// Change what a click selects.  Clears the selection.
+    pub fn set_mode(&mut self, mode: SelectionMode) {
+        self.mode = mode;
+        self.clear();
+    }
+
+    /// Called with the new selection whenever it changes, on the UI thread.
+    pub fn on_selection_changed(&mut self, listener: impl FnMut(&SelectionModel) + Send + 'static) {
+        self.listeners.push(Box::new(listener));
+    }
+
+    /// Area selected by clicking a cell in the current mode.
+    fn unit(&self, row: usize, col: usize) -> (Range<usize>, Range<usize>) {
+        self.area((row, col), (row, col))
+    }
+
+    /// Area between two cells in the current mode.
+    fn area(&self, a: (usize, usize), b: (usize, usize)) -> (Range<usize>, Range<usize>) {
+        let rows = a.0.min(b.0)..a.0.max(b.0) + 1;
+        let cols = a.1.min(b.1)..a.1.max(b.1) + 1;
+        match self.mode {
+            SelectionMode::Row => (rows, 0..usize::MAX),
+            SelectionMode::Cell => (rows, cols),
+            SelectionMode::Column => (0..usize::MAX, cols),
+        }
+    }
+
+    /// Select as a click would.  Ctrl toggles the clicked row, cell or column, keeping the rest of the selection.
+    /// Shift selects from the last clicked cell, replacing the selection unless Ctrl is also held.
+    pub fn click(&mut self, row: usize, col: usize, ctrl: bool, shift: bool) {
+        match (self.anchor, ctrl, shift) {
+            (Some(anchor), _, true) => {
+                if !ctrl {
+                    self.ranges.clear();
+                }
+                self.ranges.push(self.area(anchor, (row, col)));
+                self.extending = true;
+            }
+            (_, true, _) => {
+                let unit = self.unit(row, col);
+                if self.is_selected(row, col) {
+                    self.ranges = mem::take(&mut self.ranges)
+                        .into_iter()
+                        .flat_map(|r| subtract(r, &unit))
+                        .collect();
+                    self.extending = false;
+                } else {
+                    self.ranges.push(unit);
+                    self.extending = true;
+                }
+                self.anchor = Some((row, col));
+            }
+            _ => {
+                self.ranges = vec![self.unit(row, col)];
+                self.anchor = Some((row, col));
+                self.extending = true;
+            }
+        }
+        self.touch();
+    }
+
+    /// Extend the selection from the last clicked cell, as when dragging or using Shift+arrow keys.
+    pub fn extend_to(&mut self, row: usize, col: usize) {
+        match self.anchor {
+            Some(anchor) => {
+                let area = self.area(anchor, (row, col));
+                if self.extending {
+                    if self.ranges.last() == Some(&area) {
+                        return;
+                    }
+                    self.ranges.pop();
+                }
+                self.ranges.push(area);
+                self.extending = true;
+                self.touch();
+            }
+            None => self.click(row, col, false, false),
+        }
+    }
+
+    /// Select a range of rows, in any mode.
+    pub fn select_rows(&mut self, rows: Range<usize>) {
+        self.ranges.clear();
+        if !rows.is_empty() {
+            self.anchor = Some((rows.start, 0));
+            self.ranges.push((rows, 0..usize::MAX));
+        }
+        self.extending = false;
+        self.touch();
+    }
+
+    /// Select several ranges of rows, in any mode, as when restoring a saved selection.
+    pub fn select_row_ranges(&mut self, ranges: &[Range<usize>]) {
+        self.ranges = ranges
+            .iter()
+            .filter(|rows| !rows.is_empty())
+            .map(|rows| (rows.clone(), 0..usize::MAX))
+            .collect();
+        self.anchor = self.ranges.first().map(|(rows, _)| (rows.start, 0));
+        self.extending = false;
+        self.touch();
+    }
+
+    /// Select a whole row, as when clicking its row header, or with Shift the rows from the last one clicked.
+    pub fn click_row(&mut self, row: usize, shift: bool) {
+        let first = match (self.anchor, shift) {
+            (Some((anchor, _)), true) => anchor,
+            _ => row,
+        };
+        self.select_rows(first.min(row)..first.max(row) + 1);
+        self.anchor = Some((first, 0));
+    }
+
+    /// Select every cell of a table.
+    pub fn select_all(&mut self, rows: usize, cols: usize) {
+        self.ranges.clear();
+        if rows > 0 && cols > 0 {
+            self.ranges.push((0..rows, 0..cols));
+        }
+        self.extending = false;
+        self.touch();
+    }
+
+    pub fn clear(&mut self) {
+        if !self.ranges.is_empty() {
+            self.ranges.clear();
+            self.touch();
+        }
+        self.anchor = None;
+        self.extending = false;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn is_selected(&self, row: usize, col: usize) -> bool {
+        self.ranges
+            .iter()
+            .any(|(rows, cols)| rows.contains(&row) && cols.contains(&col))
+    }
+
+    /// True if any cell of the row is selected.
+    pub fn is_row_selected(&self, row: usize) -> bool {
+        self.ranges.iter().any(|(rows, _)| rows.contains(&row))
+    }
+
+    /// Selected areas as (rows, columns).  Whole rows or columns end at `usize::MAX`.
+    pub fn ranges(&self) -> &[(Range<usize>, Range<usize>)] {
+        &self.ranges
+    }
+
+    /// Rows with any selected cell, as sorted ranges that end no later than `count`.
+    pub fn selected_rows(&self, count: usize) -> Vec<Range<usize>> {
+        merge(self.ranges.iter().map(|(rows, _)| rows.clone()), count)
+    }
+
+    /// Columns with any selected cell, as sorted ranges that end no later than `count`.
+    pub fn selected_cols(&self, count: usize) -> Vec<Range<usize>> {
+        merge(self.ranges.iter().map(|(_, cols)| cols.clone()), count)
+    }
+
+    /// First selected row and column, where pasting starts.
+    pub fn top_left(&self) -> Option<(usize, usize)> {
+        let row = self.ranges.iter().map(|(rows, _)| rows.start).min()?;
+        let col = self.ranges.iter().map(|(_, cols)| cols.start).min()?;
+        Some((row, col))
+    }
+
+    fn touch(&mut self) {
+        self.changed = true;
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// Remember the selected rows, the cursor and the top row by key, before the model moves rows.
+    pub(crate) fn remember_rows<M: SimpleModel + ?Sized>(
+        &mut self,
+        model: &mut M,
+        cursor: &CellCursor,
+        top: usize,
+    ) {
+        self.keys = self.row_keys(model, cursor, top);
+    }
+
+    /// Keys of the selected rows, the cursor and the top row, or None if the model has no keys.
+    fn row_keys<M: SimpleModel + ?Sized>(
+        &self,
+        model: &mut M,
+        cursor: &CellCursor,
+        top: usize,
+    ) -> Option<RowKeys> {
+        let count = model.row_info().count;
+        if count == 0 || model.row_key(0).is_none() {
+            return None;
+        }
+        let mut key = |row: usize| {
+            if row < count {
+                model.row_key(row as i32)
+            } else {
+                None
+            }
+        };
+        let ranges = self
+            .ranges
+            .iter()
+            .map(|(rows, cols)| {
+                let keys =
+                    (rows.end != usize::MAX).then(|| rows.clone().filter_map(&mut key).collect());
+                (keys, cols.clone())
+            })
+            .collect();
+        let anchor = self
+            .anchor
+            .and_then(|(row, col)| key(row).map(|k| (k, col)));
+        let cursor = if cursor.row >= 0 {
+            key(cursor.row as usize).map(|k| (cursor.row, k))
+        } else {
+            None
+        };
+        let top = key(top).map(|k| (top, k));
+        Some(RowKeys {
+            generation: self.generation,
+            count,
+            ranges,
+            anchor,
+            cursor,
+            top,
+        })
+    }
+
+    /// Move the selection, and the cursor and top row if they haven't moved, to where the remembered rows are now.
+    /// Returns the new top row, if it changed.
+    pub(crate) fn follow_rows<M: SimpleModel + ?Sized>(
+        &mut self,
+        model: &mut M,
+        cursor: &mut CellCursor,
+        top: usize,
+    ) -> Option<usize> {
+        let Some(keys) = self.keys.take() else {
+            self.remember_rows(model, cursor, top);
+            return None;
+        };
+        // nothing to do when the remembered rows are still where they were
+        let current = self.row_keys(model, cursor, top);
+        if current.as_ref() == Some(&keys) {
+            self.keys = current;
+            return None;
+        }
+        let count = model.row_info().count;
+        let index: HashMap<u64, usize> = (0..count)
+            .filter_map(|row| model.row_key(row as i32).map(|k| (k, row)))
+            .collect();
+        if keys.generation == self.generation {
+            let mut ranges = Vec::new();
+            for ((row_keys, cols), (old_rows, _)) in keys.ranges.into_iter().zip(&self.ranges) {
+                match row_keys {
+                    Some(row_keys) => {
+                        let mut rows: Vec<usize> = row_keys
+                            .iter()
+                            .filter_map(|k| index.get(k).copied())
+                            .collect();
+                        rows.sort_unstable();
+                        ranges.extend(runs(&rows).into_iter().map(|r| (r, cols.clone())));
+                    }
+                    None => ranges.push((old_rows.clone(), cols)),
+                }
+            }
+            let anchor = keys
+                .anchor
+                .and_then(|(k, col)| index.get(&k).map(|&row| (row, col)));
+            if ranges != self.ranges || anchor != self.anchor {
+                self.ranges = ranges;
+                self.anchor = anchor;
+                self.touch();
+            }
+        }
+        if let Some((row, k)) = keys.cursor {
+            if cursor.row == row {
+                if let Some(&new_row) = index.get(&k) {
+                    cursor.row = new_row as i32;
+                }
+            }
+        }
+        let new_top = keys
+            .top
+            .filter(|(row, _)| *row == top)
+            .and_then(|(_, k)| index.get(&k).copied())
+            .filter(|new_top| *new_top != top);
+        self.remember_rows(model, cursor, new_top.unwrap_or(top));
+        new_top
+    }
+
+    /// Call the listeners if the selection has changed.  The lock is released while listeners run,
+    /// so they can use the table.
+    pub(crate) fn notify(selection: &Arc<Mutex<SelectionModel>>) {
+        let (snapshot, mut listeners) = {
+            let mut selection = selection.lock().unwrap();
+            if !selection.changed {
+                return;
+            }
+            selection.changed = false;
+            (selection.clone(), mem::take(&mut selection.listeners))
+        };
+        for listener in listeners.iter_mut() {
+            listener(&snapshot);
+        }
+        let mut selection = selection.lock().unwrap();
+        // keep listeners added while notifying
+        listeners.append(&mut selection.listeners);
+        selection.listeners = listeners;
+    }
+
+    /// Like `notify`, but calls the listeners on the UI thread, for changes found on a timer thread.
+    pub(crate) fn notify_ui(selection: &Arc<Mutex<SelectionModel>>) {
+        if !selection.lock().unwrap().changed {
+            return;
+        }
+        let selection = selection.clone();
+        app::awake_callback(move || SelectionModel::notify(&selection));
+    }
+}
+
+/// Parts of `area` outside of `hole`.
+fn subtract(
+    area: (Range<usize>, Range<usize>),
+    hole: &(Range<usize>, Range<usize>),
+) -> Vec<(Range<usize>, Range<usize>)> {
+    let (rows, cols) = area;
+    let overlap_rows = rows.start.max(hole.0.start)..rows.end.min(hole.0.end);
+    let overlap_cols = cols.start.max(hole.1.start)..cols.end.min(hole.1.end);
+    if overlap_rows.is_empty() || overlap_cols.is_empty() {
+        return vec![(rows, cols)];
+    }
+    let pieces = [
+        // above, below, then left and right of the overlap
+        (rows.start..overlap_rows.start, cols.clone()),
+        (overlap_rows.end..rows.end, cols.clone()),
+        (overlap_rows.clone(), cols.start..overlap_cols.start),
+        (overlap_rows, overlap_cols.end..cols.end),
+    ];
+    pieces
+        .into_iter()
+        .filter(|(r, c)| !r.is_empty() && !c.is_empty())
+        .collect()
+}
+
+/// Runs of consecutive rows, from sorted rows.
+fn runs(rows: &[usize]) -> Vec<Range<usize>> {
+    let mut runs: Vec<Range<usize>> = Vec::new();
+    for &row in rows {
+        match runs.last_mut() {
+            Some(run) if run.end == row => run.end += 1,
+            Some(run) if run.end > row => {}
+            _ => runs.push(row..row + 1),
+        }
+    }
+    runs
+}
+
+/// Sort and join ranges, clipped to `0..count`.
+fn merge(ranges: impl Iterator<Item = Range<usize>>, count: usize) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = ranges
+        .map(|r| r.start.min(count)..r.end.min(count))
+        .filter(|r| !r.is_empty())
+        .collect();
+    ranges.sort_by_key(|r| r.start);
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for r in ranges {
+        match merged.last_mut() {
+            Some(last) if r.start <= last.end => last.end = last.end.max(r.end),
+            _ => merged.push(r),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_model::{Order, SimpleCell, VecColumn, VecModel};
+
+    const ALL: usize = usize::MAX;
+
+    #[test]
+    fn click_modes() {
+        let mut selection = SelectionModel::new(SelectionMode::Row);
+        selection.click(2, 1, false, false);
+        assert_eq!(selection.ranges(), &[(2..3, 0..ALL)]);
+        selection.set_mode(SelectionMode::Cell);
+        assert!(selection.is_empty());
+        selection.click(2, 1, false, false);
+        assert_eq!(selection.ranges(), &[(2..3, 1..2)]);
+        selection.set_mode(SelectionMode::Column);
+        selection.click(2, 1, false, false);
+        assert_eq!(selection.ranges(), &[(0..ALL, 1..2)]);
+    }
+
+    #[test]
+    fn shift_and_drag_extend() {
+        let mut selection = SelectionModel::new(SelectionMode::Cell);
+        selection.click(4, 3, false, false);
+        selection.click(2, 1, false, true);
+        assert_eq!(selection.ranges(), &[(2..5, 1..4)]);
+        selection.extend_to(5, 3);
+        assert_eq!(selection.ranges(), &[(4..6, 3..4)]);
+        assert_eq!(selection.top_left(), Some((4, 3)));
+        // ctrl+shift adds to the selection
+        selection.click(0, 0, true, false);
+        selection.click(1, 0, true, true);
+        assert_eq!(
+            selection.ranges(),
+            &[(4..6, 3..4), (0..1, 0..1), (0..2, 0..1)]
+        );
+        assert_eq!(selection.top_left(), Some((0, 0)));
+    }
+
+    #[test]
+    fn ctrl_click_toggles() {
+        let mut selection = SelectionModel::new(SelectionMode::Cell);
+        selection.select_all(3, 3);
+        selection.click(1, 1, true, false);
+        assert!(!selection.is_selected(1, 1));
+        assert_eq!(
+            selection.ranges(),
+            &[(0..1, 0..3), (2..3, 0..3), (1..2, 0..1), (1..2, 2..3)]
+        );
+        selection.click(1, 1, true, false);
+        assert!(selection.is_selected(1, 1));
+    }
+
+    #[test]
+    fn subtract_pieces() {
+        assert_eq!(subtract((0..2, 0..2), &(5..6, 0..2)), vec![(0..2, 0..2)]);
+        assert_eq!(subtract((0..2, 0..2), &(0..2, 0..2)), vec![]);
+        assert_eq!(
+            subtract((0..4, 0..ALL), &(1..2, 0..ALL)),
+            vec![(0..1, 0..ALL), (2..4, 0..ALL)]
+        );
+    }
+
+    #[test]
+    fn selected_rows_and_cols() {
+        let mut selection = SelectionModel::new(SelectionMode::Row);
+        selection.click(5, 0, false, false);
+        selection.click(1, 0, true, false);
+        selection.click(2, 0, true, false);
+        selection.click_row(8, false);
+        selection.click_row(6, true);
+        assert_eq!(selection.selected_rows(10), vec![6..9]);
+        selection.click(1, 0, true, false);
+        assert_eq!(selection.selected_rows(7), vec![1..2, 6..7]);
+        assert_eq!(selection.selected_cols(4), vec![0..4]);
+        assert!(selection.is_row_selected(1));
+        assert!(!selection.is_row_selected(5));
+    }
+
+    #[test]
+    fn select_row_ranges() {
+        let mut selection = SelectionModel::new(SelectionMode::Cell);
+        selection.select_row_ranges(&[1..3, 4..4, 6..7]);
+        assert_eq!(selection.selected_rows(10), vec![1..3, 6..7]);
+        assert!(!selection.is_row_selected(4));
+        assert_eq!(selection.top_left(), Some((1, 0)));
+    }
+
+    #[test]
+    fn merge_and_runs() {
+        assert_eq!(
+            merge([4..6, 0..2, 1..3, 9..20].into_iter(), 10),
+            vec![0..3, 4..6, 9..10]
+        );
+        assert_eq!(merge(std::iter::once(12..20), 10), vec![]);
+        assert_eq!(runs(&[1, 2, 3, 5, 5, 7, 8]), vec![1..4, 5..6, 7..9]);
+        assert_eq!(runs(&[]), vec![]);
+    }
+
+    #[test]
+    fn notify_only_changes() {
+        let selection = Arc::new(Mutex::new(SelectionModel::new(SelectionMode::Row)));
+        let seen: Arc<Mutex<Vec<usize>>> = Default::default();
+        let log = seen.clone();
+        selection
+            .lock()
+            .unwrap()
+            .on_selection_changed(move |s| log.lock().unwrap().push(s.ranges().len()));
+        SelectionModel::notify(&selection);
+        selection.lock().unwrap().click(1, 0, false, false);
+        SelectionModel::notify(&selection);
+        SelectionModel::notify(&selection);
+        selection.lock().unwrap().clear();
+        SelectionModel::notify(&selection);
+        assert_eq!(*seen.lock().unwrap(), vec![1, 0]);
+    }
+
+    fn keyed(names: &[&str]) -> VecModel<(u64, String)> {
+        let items = names
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (i as u64, n.to_string()))
+            .collect();
+        VecModel::new(
+            items,
+            vec![VecColumn::new("Name", 50, |i: &(u64, String)| {
+                SimpleCell::Text(i.1.clone())
+            })],
+        )
+        .with_row_key(|i| i.0)
+    }
+
+    #[test]
+    fn selection_follows_sorted_rows() {
+        let mut model = keyed(&["d", "a", "c", "b"]);
+        let mut selection = SelectionModel::new(SelectionMode::Row);
+        let mut cursor = CellCursor { row: 2, col: 0 };
+        selection.click(0, 0, false, false);
+        selection.click(2, 0, true, false);
+        selection.remember_rows(&mut model, &cursor, 3);
+        model.sort(0, Order::Ascending);
+        // a b c d: d moved from 0 to 3, c from 2 to 2, b (the top row) from 3 to 1
+        let top = selection.follow_rows(&mut model, &mut cursor, 3);
+        assert_eq!(top, Some(1));
+        assert_eq!(selection.selected_rows(4), vec![2..4]);
+        assert_eq!(cursor.row, 2);
+        model.sort(0, Order::Descending);
+        // d c b a
+        let top = selection.follow_rows(&mut model, &mut cursor, 1);
+        assert_eq!(top, Some(2));
+        assert_eq!(selection.selected_rows(4), vec![0..2]);
+        assert_eq!(cursor.row, 1);
+    }
+
+    #[test]
+    fn removed_rows_leave_the_selection() {
+        let mut model = keyed(&["a", "b", "c"]);
+        let mut selection = SelectionModel::new(SelectionMode::Row);
+        let mut cursor = CellCursor::default();
+        selection.click_row(1, false);
+        selection.click_row(2, true);
+        selection.remember_rows(&mut model, &cursor, 0);
+        model.items_mut().remove(1);
+        selection.follow_rows(&mut model, &mut cursor, 0);
+        assert_eq!(selection.selected_rows(2), vec![1..2]);
+    }
+
+    #[test]
+    fn appended_rows_keep_the_selection() {
+        let mut model = keyed(&["a", "b"]);
+        let mut selection = SelectionModel::new(SelectionMode::Row);
+        let mut cursor = CellCursor { row: 1, col: 0 };
+        selection.click_row(1, false);
+        selection.remember_rows(&mut model, &cursor, 0);
+        let generation = selection.generation;
+        model.items_mut().push((2, "c".to_string()));
+        assert_eq!(selection.follow_rows(&mut model, &mut cursor, 0), None);
+        assert_eq!(selection.selected_rows(3), vec![1..2]);
+        assert_eq!(selection.generation, generation);
+        assert_eq!(cursor.row, 1);
+    }
+
+    #[test]
+    fn new_selection_is_not_moved() {
+        let mut model = keyed(&["b", "a"]);
+        let mut selection = SelectionModel::new(SelectionMode::Row);
+        let mut cursor = CellCursor::default();
+        selection.click(0, 0, false, false);
+        selection.remember_rows(&mut model, &cursor, 0);
+        // changed by the user after the rows were remembered
+        selection.click(1, 0, false, false);
+        model.sort(0, Order::Ascending);
+        selection.follow_rows(&mut model, &mut cursor, 0);
+        assert_eq!(selection.selected_rows(2), vec![1..2]);
+    }
+
+    #[test]
+    fn models_without_keys_keep_positions() {
+        let mut model = VecModel::new(
+            vec!["b", "a"],
+            vec![VecColumn::new("Name", 50, |s: &&str| {
+                SimpleCell::Text(s.to_string())
+            })],
+        );
+        let mut selection = SelectionModel::new(SelectionMode::Row);
+        let mut cursor = CellCursor::default();
+        selection.click(0, 0, false, false);
+        selection.remember_rows(&mut model, &cursor, 0);
+        model.sort(0, Order::Ascending);
+        assert_eq!(selection.follow_rows(&mut model, &mut cursor, 0), None);
+        assert_eq!(selection.selected_rows(2), vec![0..1]);
+    }
+}
@@ -1,9 +1,20 @@
-use std::ops::Range;
+use std::{
+    cmp::Ordering,
+    io::{self, Write},
+    ops::Range,
+};
 
-use fltk::widget::Widget;
+use chrono::NaiveDateTime;
+use fltk::{
+    enums::{Align, Color, Font},
+    image::SharedImage,
+    widget::Widget,
+};
+
+use crate::model_events::ModelEvents;
 
 // Sort order
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Order {
     Ascending,
     Descending,
@@ -25,9 +36,97 @@ impl Order {
     }
 }
 
+/// Sort keys, highest priority first.  Maintained by the table widgets as headers are clicked.
+#[derive(Debug, Clone, Default)]
+pub struct SortKeys {
+    pub keys: Vec<(usize, Order)>,
+}
+impl SortKeys {
+    /// Update for a click on a column header.
+    /// A click sorts by the column alone, cycling its order.
+    /// A shift click (`extend`) appends the column to the keys, or cycles its order, removing it after descending.
+    pub fn click(&mut self, col: usize, extend: bool) {
+        if extend {
+            self.keys.retain(|(_, o)| *o != Order::None);
+            match self.keys.iter().position(|(c, _)| *c == col) {
+                Some(i) => match self.keys[i].1.next() {
+                    Order::None => {
+                        self.keys.remove(i);
+                    }
+                    order => self.keys[i].1 = order,
+                },
+                None => self.keys.push((col, Order::Ascending)),
+            }
+        } else {
+            let pos = self.keys.iter().position(|(c, _)| *c == col);
+            let order = match pos {
+                Some(i) if self.keys.len() == 1 => self.keys[i].1.next(),
+                _ => Order::Ascending,
+            };
+            self.keys = vec![(col, order)];
+        }
+    }
+
+    /// Priority (0 is highest) and order of a column, if it is sorted.
+    pub fn get(&self, col: usize) -> Option<(usize, Order)> {
+        self.keys
+            .iter()
+            .enumerate()
+            .find(|(_, (c, o))| *c == col && *o != Order::None)
+            .map(|(i, (_, o))| (i, *o))
+    }
+}
+
 /// Custom renderer without the overhead of creating a full widget
 pub trait DrawDelegate {
     fn draw(&self, row: i32, col: i32, x: i32, y: i32, w: i32, h: i32, selected: bool);
+
+    /// Text for copying, exporting, filtering and sorting, if the cell shows any.
+    fn text(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Optional styling for a row or cell.  Unset fields use the table's defaults.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CellStyle {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub font: Option<Font>,
+    pub size: Option<i32>,
+    pub bold: bool,
+    pub italic: bool,
+    pub align: Option<Align>,
+    /// space between the cell border and its text
+    pub padding: Option<i32>,
+}
+impl CellStyle {
+    /// Fields set in `other` replace fields in this style.
+    pub fn merge(self, other: CellStyle) -> CellStyle {
+        CellStyle {
+            fg: other.fg.or(self.fg),
+            bg: other.bg.or(self.bg),
+            font: other.font.or(self.font),
+            size: other.size.or(self.size),
+            bold: self.bold || other.bold,
+            italic: self.italic || other.italic,
+            align: other.align.or(self.align),
+            padding: other.padding.or(self.padding),
+        }
+    }
+
+    /// The style's font, or the default, with bold and italic applied.
+    /// Bold and italic only apply to the builtin Helvetica, Courier and Times fonts.
+    pub fn font_or(&self, default: Font) -> Font {
+        let font = self.font.unwrap_or(default);
+        let index = font.bits();
+        if index < 12 && (self.bold || self.italic) {
+            let family = index - index % 4;
+            Font::by_index((family + self.bold as i32 + 2 * self.italic as i32) as usize)
+        } else {
+            font
+        }
+    }
 }
 
 pub enum RowHeight {
@@ -70,20 +169,119 @@ impl ColumnInfo {
 }
 
 pub enum SimpleCell {
+    /// Left aligned text, on one line per embedded newline
     Text(String),
+    /// Text with its own alignment, wrapped to the cell width if `wrap` is set.
+    /// Kept apart from `Text` so that models built with `Text(String)` don't have to change.
+    AlignedText {
+        text: String,
+        align: Align,
+        wrap: bool,
+    },
+    Int(i64),
+    /// Number shown with `precision` decimal places
+    Float {
+        value: f64,
+        precision: usize,
+    },
+    /// Drawn as a checkbox
+    Bool(bool),
+    /// Shown using a chrono format string, such as "%Y-%m-%d %H:%M:%S"
+    DateTime {
+        value: NaiveDateTime,
+        format: String,
+    },
+    /// Bar filled from 0.0 to 1.0
+    Progress(f32),
+    Image(SharedImage),
     Delegate(Box<dyn DrawDelegate>),
     Widget(Widget),
     None,
 }
 
 impl SimpleCell {
-    /// Used by copy/paste and export functions
+    /// Text of text cells
     pub fn as_str(&self) -> Option<&str> {
         match self {
-            SimpleCell::Text(s) => Some(s),
+            SimpleCell::Text(s) | SimpleCell::AlignedText { text: s, .. } => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Used by copy/paste and export functions.  Typed values are formatted here, so models don't have to.
+    pub fn to_text(&self) -> Option<String> {
+        match self {
+            SimpleCell::Text(s) | SimpleCell::AlignedText { text: s, .. } => Some(s.clone()),
+            SimpleCell::Int(i) => Some(i.to_string()),
+            SimpleCell::Float { value, precision } => Some(format!("{value:.precision$}")),
+            SimpleCell::Bool(b) => Some(b.to_string()),
+            SimpleCell::DateTime { value, format } => {
+                // an invalid format falls back to the default format, rather than panicking
+                let mut text = String::new();
+                match std::fmt::Write::write_fmt(
+                    &mut text,
+                    format_args!("{}", value.format(format)),
+                ) {
+                    Ok(_) => Some(text),
+                    Err(_) => Some(value.to_string()),
+                }
+            }
+            SimpleCell::Progress(p) => Some(format!("{:.0}%", p * 100.0)),
+            SimpleCell::Delegate(d) => d.text(),
+            SimpleCell::Image(_) | SimpleCell::Widget(_) | SimpleCell::None => None,
+        }
+    }
+
+    /// Numeric value, for comparing numbers of different types.
+    pub(crate) fn number(&self) -> Option<f64> {
+        match self {
+            SimpleCell::Int(i) => Some(*i as f64),
+            SimpleCell::Float { value, .. } => Some(*value),
+            SimpleCell::Progress(p) => Some(*p as f64),
             _ => None,
         }
     }
+
+    /// Compare by value when both cells have the same kind of value, otherwise by text.
+    pub fn compare(&self, other: &SimpleCell) -> Ordering {
+        match (self, other) {
+            (SimpleCell::Int(a), SimpleCell::Int(b)) => a.cmp(b),
+            (SimpleCell::Bool(a), SimpleCell::Bool(b)) => a.cmp(b),
+            (SimpleCell::DateTime { value: a, .. }, SimpleCell::DateTime { value: b, .. }) => {
+                a.cmp(b)
+            }
+            _ => match (self.number(), other.number()) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                _ => self.to_text().cmp(&other.to_text()),
+            },
+        }
+    }
+
+    /// Alignment used when the cell style doesn't set one.  Numbers are right aligned.
+    pub fn align(&self) -> Align {
+        match self {
+            SimpleCell::AlignedText { align, wrap, .. } if *wrap => *align | Align::Wrap,
+            SimpleCell::AlignedText { align, .. } => *align,
+            SimpleCell::Int(_) | SimpleCell::Float { .. } => Align::Right,
+            SimpleCell::Bool(_) | SimpleCell::Progress(_) | SimpleCell::Image(_) => Align::Center,
+            _ => Align::Left,
+        }
+    }
+}
+
+/// How a cell is edited. The editor's value is passed to `SimpleModel::set_cell` as text.
+#[derive(Debug, Clone)]
+pub enum EditorKind {
+    Text,
+    /// Spinner, producing an integer
+    Integer {
+        min: i64,
+        max: i64,
+    },
+    /// Checkbox, producing "true" or "false"
+    Check,
+    /// Drop down list of allowed values
+    Choice(Vec<String>),
 }
 
 /// Table model trait. Implementations of this trait will describe how to display a table.
@@ -94,10 +292,871 @@ pub trait SimpleModel {
 
     fn get_cell(&mut self, row: i32, col: i32) -> SimpleCell;
 
+    /// Optional change notification.  Tables subscribe with `redraw_on_events`, so they repaint as the
+    /// model changes instead of polling with `redraw_on`.
+    fn events(&mut self) -> Option<ModelEvents> {
+        None
+    }
+
     /// Popup help.
     fn hover(&self, _row: i32, _col: i32) -> Option<String> {
         None
     }
     /// Optional sorting. Activated by clicking on a header.
     fn sort(&mut self, _col: usize, _order: Order) {}
+    /// Optional sorting by several columns, highest priority first. Activated by shift clicking on headers.
+    /// By default `sort` is called for each key, lowest priority first, which is correct when `sort` is stable.
+    fn sort_multi(&mut self, keys: &[(usize, Order)]) {
+        for (col, order) in keys.iter().rev() {
+            self.sort(*col, *order);
+        }
+    }
+
+    /// Label for a row in the row header, when the table shows one.  Rows are numbered from 1 by default.
+    fn row_header(&mut self, row: i32) -> Option<String> {
+        Some((row + 1).to_string())
+    }
+
+    /// Optional identity of the record in a row.  When provided, the selection, focused cell and scroll
+    /// position follow records as rows are sorted, inserted or removed.
+    fn row_key(&mut self, _row: i32) -> Option<u64> {
+        None
+    }
+
+    /// Optional styling for a whole row, e.g. to highlight alarms.
+    fn row_style(&mut self, _row: i32) -> CellStyle {
+        CellStyle::default()
+    }
+    /// Optional styling for a cell.  Fields set here replace those from `row_style`.
+    fn cell_style(&mut self, _row: i32, _col: i32) -> CellStyle {
+        CellStyle::default()
+    }
+
+    /// Optional editing. Editable cells are opened for editing by double click or F2.
+    fn is_editable(&self, _row: i32, _col: i32) -> bool {
+        false
+    }
+    /// The kind of editor used for an editable cell.
+    fn editor(&self, _row: i32, _col: i32) -> EditorKind {
+        EditorKind::Text
+    }
+    /// Store an edited value. Returns a message when the value is rejected.
+    fn set_cell(&mut self, _row: i32, _col: i32, _value: &str) -> Result<(), String> {
+        Err("Table is read only".to_string())
+    }
+    /// Run the cell's action when Enter is pressed on it.  Returns false if the cell has no action,
+    /// in which case an editable cell is edited.
+    fn activate(&mut self, _row: i32, _col: i32) -> bool {
+        false
+    }
+    /// Optional expanding and collapsing of rows, as in a tree.  Called when + or - is pressed on a row.
+    /// Returns true if rows were shown or hidden.
+    fn expand(&mut self, _row: i32, _expand: bool) -> bool {
+        false
+    }
+    /// Optional action for a click on a cell, such as collapsing a group.  Returns true if rows were
+    /// shown or hidden, in which case the click doesn't select.
+    fn click(&mut self, _row: i32, _col: i32) -> bool {
+        false
+    }
+    /// Paste tab separated rows, with the top left value going to (row, col).
+    /// By default each value is passed to `set_cell`.  Values past the last row or column, or for cells
+    /// that aren't editable, are skipped, so `set_cell` only receives in range, editable cells.
+    fn paste(&mut self, row: i32, col: i32, data: &str) -> Result<(), String> {
+        let rows = self.row_info().count as i32;
+        let cols = self.column_info().details.len() as i32;
+        for (r, line) in data.lines().enumerate() {
+            let r = row + r as i32;
+            if r >= rows {
+                break;
+            }
+            for (c, value) in line.split('\t').enumerate() {
+                let c = col + c as i32;
+                if c >= cols {
+                    break;
+                }
+                if self.is_editable(r, c) {
+                    self.set_cell(r, c, value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Column of a `VecModel`, built from closures over the row type.
+pub struct VecColumn<T> {
+    pub header: String,
+    pub width: u32,
+    cell: Box<dyn Fn(&T) -> SimpleCell + Send>,
+    compare: Option<CompareFn<T>>,
+    hover: Option<HoverFn<T>>,
+    edit: Option<EditFn<T>>,
+    style: Option<StyleFn<T>>,
+    action: Option<ActionFn<T>>,
+}
+type CompareFn<T> = Box<dyn Fn(&T, &T) -> Ordering + Send>;
+type HoverFn<T> = Box<dyn Fn(&T) -> Option<String> + Send>;
+type EditFn<T> = Box<dyn Fn(&mut T, &str) -> Result<(), String> + Send>;
+type FilterFn<T> = Box<dyn Fn(&T) -> bool + Send>;
+type StyleFn<T> = Box<dyn Fn(&T) -> CellStyle + Send>;
+type ActionFn<T> = Box<dyn Fn(&mut T) + Send>;
+type KeyFn<T> = Box<dyn Fn(&T) -> u64 + Send>;
+
+impl<T> VecColumn<T> {
+    pub fn new(
+        header: &str,
+        width: u32,
+        cell: impl Fn(&T) -> SimpleCell + Send + 'static,
+    ) -> VecColumn<T> {
+        VecColumn {
+            header: header.to_string(),
+            width,
+            cell: Box::new(cell),
+            compare: None,
+            hover: None,
+            edit: None,
+            style: None,
+            action: None,
+        }
+    }
+    /// Comparator used for sorting.  Without one, the cell values are compared.
+    pub fn with_compare(mut self, compare: impl Fn(&T, &T) -> Ordering + Send + 'static) -> Self {
+        self.compare = Some(Box::new(compare));
+        self
+    }
+    pub fn with_hover(mut self, hover: impl Fn(&T) -> Option<String> + Send + 'static) -> Self {
+        self.hover = Some(Box::new(hover));
+        self
+    }
+    /// Makes the column editable with a text editor.
+    pub fn with_edit(
+        mut self,
+        edit: impl Fn(&mut T, &str) -> Result<(), String> + Send + 'static,
+    ) -> Self {
+        self.edit = Some(Box::new(edit));
+        self
+    }
+    pub fn with_style(mut self, style: impl Fn(&T) -> CellStyle + Send + 'static) -> Self {
+        self.style = Some(Box::new(style));
+        self
+    }
+    /// Action run when Enter is pressed on a cell in the column.
+    pub fn with_action(mut self, action: impl Fn(&mut T) + Send + 'static) -> Self {
+        self.action = Some(Box::new(action));
+        self
+    }
+
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        match &self.compare {
+            Some(compare) => compare(a, b),
+            None => (self.cell)(a).compare(&(self.cell)(b)),
+        }
+    }
+}
+
+/// Model over a `Vec`, described by a list of columns.  Sorting, filtering, hover and editing are
+/// handled by the columns, so no `SimpleModel` functions need to be written.
+pub struct VecModel<T> {
+    items: Vec<T>,
+    columns: Vec<VecColumn<T>>,
+    row_height: u32,
+    filter: Option<FilterFn<T>>,
+    row_style: Option<StyleFn<T>>,
+    row_key: Option<KeyFn<T>>,
+    sort_keys: Vec<(usize, Order)>,
+    /// indexes into items, in display order
+    view: Vec<usize>,
+    dirty: bool,
+    events: ModelEvents,
+}
+
+impl<T> VecModel<T> {
+    pub fn new(items: Vec<T>, columns: Vec<VecColumn<T>>) -> VecModel<T> {
+        VecModel {
+            items,
+            columns,
+            row_height: 20,
+            filter: None,
+            row_style: None,
+            row_key: None,
+            sort_keys: Vec::new(),
+            view: Vec::new(),
+            dirty: true,
+            events: ModelEvents::new(),
+        }
+    }
+    pub fn with_row_height(mut self, row_height: u32) -> Self {
+        self.row_height = row_height;
+        self
+    }
+    pub fn with_row_style(mut self, style: impl Fn(&T) -> CellStyle + Send + 'static) -> Self {
+        self.row_style = Some(Box::new(style));
+        self
+    }
+    /// Identity of an item, so the selection follows items as they are sorted or added.
+    pub fn with_row_key(mut self, key: impl Fn(&T) -> u64 + Send + 'static) -> Self {
+        self.row_key = Some(Box::new(key));
+        self
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+    /// Mutable access to the items.  Sorting and filtering are reapplied on the next redraw.
+    pub fn items_mut(&mut self) -> &mut Vec<T> {
+        self.changed();
+        &mut self.items
+    }
+    pub fn push(&mut self, item: T) {
+        self.items_mut().push(item);
+    }
+
+    /// Only show items matching the filter.
+    pub fn set_filter(&mut self, filter: impl Fn(&T) -> bool + Send + 'static) {
+        self.filter = Some(Box::new(filter));
+        self.changed();
+    }
+    pub fn clear_filter(&mut self) {
+        self.filter = None;
+        self.changed();
+    }
+
+    /// Rebuild the view, and tell the tables.  Tables handle events later on the UI thread, so they see the
+    /// items after the caller's changes.
+    fn changed(&mut self) {
+        self.dirty = true;
+        self.events.reset();
+    }
+
+    /// The item displayed in a row.  Until the view is rebuilt after a change, rows whose item was
+    /// removed have no item.
+    pub fn item(&self, row: usize) -> Option<&T> {
+        self.view.get(row).and_then(|i| self.items.get(*i))
+    }
+
+    fn update_view(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+        let items = &self.items;
+        self.view = (0..items.len())
+            .filter(|i| self.filter.as_ref().is_none_or(|f| f(&items[*i])))
+            .collect();
+        let columns = &self.columns;
+        let keys = &self.sort_keys;
+        self.view.sort_by(|a, b| {
+            keys.iter()
+                .filter(|(col, _)| *col < columns.len())
+                .map(|(col, order)| match order {
+                    Order::None => Ordering::Equal,
+                    _ => order.apply(columns[*col].compare(&items[*a], &items[*b])),
+                })
+                .find(|o| *o != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        });
+    }
+}
+
+impl<T> SimpleModel for VecModel<T> {
+    fn events(&mut self) -> Option<ModelEvents> {
+        Some(self.events.clone())
+    }
+
+    fn row_info(&mut self) -> RowInfo {
+        self.update_view();
+        RowInfo {
+            count: self.view.len(),
+            height: RowHeight::All(self.row_height),
+        }
+    }
+
+    fn column_info(&mut self) -> ColumnInfo {
+        ColumnInfo {
+            details: self
+                .columns
+                .iter()
+                .map(|c| ColumnDetail {
+                    header: c.header.clone(),
+                    width: c.width,
+                })
+                .collect(),
+        }
+    }
+
+    fn get_cell(&mut self, row: i32, col: i32) -> SimpleCell {
+        match (self.item(row as usize), self.columns.get(col as usize)) {
+            (Some(item), Some(column)) => (column.cell)(item),
+            _ => SimpleCell::None,
+        }
+    }
+
+    fn hover(&self, row: i32, col: i32) -> Option<String> {
+        let hover = self.columns.get(col as usize)?.hover.as_ref()?;
+        hover(self.item(row as usize)?)
+    }
+
+    fn row_key(&mut self, row: i32) -> Option<u64> {
+        let key = self.row_key.as_ref()?;
+        self.item(row as usize).map(key)
+    }
+
+    fn row_style(&mut self, row: i32) -> CellStyle {
+        match (self.item(row as usize), &self.row_style) {
+            (Some(item), Some(style)) => style(item),
+            _ => CellStyle::default(),
+        }
+    }
+
+    fn cell_style(&mut self, row: i32, col: i32) -> CellStyle {
+        let style = self
+            .columns
+            .get(col as usize)
+            .and_then(|c| c.style.as_ref());
+        match (self.item(row as usize), style) {
+            (Some(item), Some(style)) => style(item),
+            _ => CellStyle::default(),
+        }
+    }
+
+    fn sort(&mut self, col: usize, order: Order) {
+        self.sort_multi(&[(col, order)]);
+    }
+
+    fn sort_multi(&mut self, keys: &[(usize, Order)]) {
+        self.sort_keys = keys.to_vec();
+        self.dirty = true;
+        self.update_view();
+    }
+
+    fn is_editable(&self, _row: i32, col: i32) -> bool {
+        self.columns
+            .get(col as usize)
+            .is_some_and(|c| c.edit.is_some())
+    }
+
+    fn set_cell(&mut self, row: i32, col: i32, value: &str) -> Result<(), String> {
+        let edit = self
+            .columns
+            .get(col as usize)
+            .and_then(|c| c.edit.as_ref())
+            .ok_or("Column is read only")?;
+        let item = self
+            .view
+            .get(row as usize)
+            .and_then(|i| self.items.get_mut(*i))
+            .ok_or("No such row")?;
+        edit(item, value)?;
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn activate(&mut self, row: i32, col: i32) -> bool {
+        let action = self
+            .columns
+            .get(col as usize)
+            .and_then(|c| c.action.as_ref());
+        let item = self
+            .view
+            .get(row as usize)
+            .and_then(|i| self.items.get_mut(*i));
+        match (action, item) {
+            (Some(action), Some(item)) => {
+                action(item);
+                self.dirty = true;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Which rows of a table to export.
+#[derive(Debug, Clone, Copy)]
+pub enum ExportRows {
+    All,
+    Selection,
+    Visible,
+}
+
+/// Writes table cells in a file format. See `export`.
+/// Cells keep their type, so formats with typed values can write numbers and booleans as such.
+pub trait Exporter {
+    fn write_header(&mut self, out: &mut dyn Write, headers: &[String]) -> io::Result<()>;
+    fn write_row(
+        &mut self,
+        out: &mut dyn Write,
+        headers: &[String],
+        cells: &[SimpleCell],
+    ) -> io::Result<()>;
+    fn finish(&mut self, _out: &mut dyn Write) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Export the given rows of a model.
+pub fn export<T: SimpleModel + ?Sized>(
+    model: &mut T,
+    rows: impl IntoIterator<Item = usize>,
+    exporter: &mut dyn Exporter,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let cols: Vec<usize> = (0..model.column_info().details.len()).collect();
+    export_cells(model, rows, &cols, exporter, out)
+}
+
+/// Export the given rows and columns of a model.
+pub fn export_cells<T: SimpleModel + ?Sized>(
+    model: &mut T,
+    rows: impl IntoIterator<Item = usize>,
+    cols: &[usize],
+    exporter: &mut dyn Exporter,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let details = model.column_info().details;
+    let headers: Vec<String> = cols
+        .iter()
+        .filter_map(|c| details.get(*c))
+        .map(|d| d.header.clone())
+        .collect();
+    exporter.write_header(out, &headers)?;
+    for row in rows {
+        let cells: Vec<SimpleCell> = cols
+            .iter()
+            .filter(|c| **c < details.len())
+            .map(|col| model.get_cell(row as i32, *col as i32))
+            .collect();
+        exporter.write_row(out, &headers, &cells)?;
+    }
+    exporter.finish(out)
+}
+
+/// RFC 4180 comma separated values.
+pub struct CsvExporter {
+    pub header: bool,
+}
+impl Default for CsvExporter {
+    fn default() -> Self {
+        CsvExporter { header: true }
+    }
+}
+impl CsvExporter {
+    fn write_line(out: &mut dyn Write, cells: &[String]) -> io::Result<()> {
+        let line: Vec<String> = cells
+            .iter()
+            .map(|c| {
+                if c.contains([',', '"', '\r', '\n']) {
+                    format!("\"{}\"", c.replace('"', "\"\""))
+                } else {
+                    c.clone()
+                }
+            })
+            .collect();
+        write!(out, "{}\r\n", line.join(","))
+    }
+}
+impl Exporter for CsvExporter {
+    fn write_header(&mut self, out: &mut dyn Write, headers: &[String]) -> io::Result<()> {
+        if self.header {
+            Self::write_line(out, headers)?;
+        }
+        Ok(())
+    }
+    fn write_row(
+        &mut self,
+        out: &mut dyn Write,
+        _headers: &[String],
+        cells: &[SimpleCell],
+    ) -> io::Result<()> {
+        Self::write_line(out, &cell_texts(cells))
+    }
+}
+
+/// Tab separated values, as used by spreadsheets on the clipboard.
+/// Tabs and newlines inside of cells are replaced with spaces.
+pub struct TsvExporter {
+    pub header: bool,
+}
+impl Default for TsvExporter {
+    fn default() -> Self {
+        TsvExporter { header: true }
+    }
+}
+impl TsvExporter {
+    fn write_line(out: &mut dyn Write, cells: &[String]) -> io::Result<()> {
+        let line: Vec<String> = cells
+            .iter()
+            .map(|c| c.replace(['\t', '\r', '\n'], " "))
+            .collect();
+        writeln!(out, "{}", line.join("\t"))
+    }
+}
+impl Exporter for TsvExporter {
+    fn write_header(&mut self, out: &mut dyn Write, headers: &[String]) -> io::Result<()> {
+        if self.header {
+            Self::write_line(out, headers)?;
+        }
+        Ok(())
+    }
+    fn write_row(
+        &mut self,
+        out: &mut dyn Write,
+        _headers: &[String],
+        cells: &[SimpleCell],
+    ) -> io::Result<()> {
+        Self::write_line(out, &cell_texts(cells))
+    }
+}
+
+/// JSON array of objects keyed by column header.
+#[derive(Default)]
+pub struct JsonExporter {
+    rows: usize,
+}
+impl Exporter for JsonExporter {
+    fn write_header(&mut self, out: &mut dyn Write, _headers: &[String]) -> io::Result<()> {
+        self.rows = 0;
+        write!(out, "[")
+    }
+    fn write_row(
+        &mut self,
+        out: &mut dyn Write,
+        headers: &[String],
+        cells: &[SimpleCell],
+    ) -> io::Result<()> {
+        if self.rows > 0 {
+            write!(out, ",")?;
+        }
+        self.rows += 1;
+        let fields: Vec<String> = headers
+            .iter()
+            .zip(cells)
+            .map(|(h, c)| format!("{}:{}", json_string(h), json_value(c)))
+            .collect();
+        write!(out, "\n  {{{}}}", fields.join(","))
+    }
+    fn finish(&mut self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "\n]")
+    }
+}
+
+/// Text of each cell, as formatted by `SimpleCell::to_text`.  Cells without text are empty.
+fn cell_texts(cells: &[SimpleCell]) -> Vec<String> {
+    cells
+        .iter()
+        .map(|c| c.to_text().unwrap_or_default())
+        .collect()
+}
+
+/// JSON value of a cell.  Numbers and booleans are unquoted.  Cells without text, and numbers JSON
+/// can't represent, are null.
+fn json_value(cell: &SimpleCell) -> String {
+    match cell {
+        SimpleCell::Int(i) => i.to_string(),
+        SimpleCell::Float { value, precision } if value.is_finite() => {
+            format!("{value:.precision$}")
+        }
+        SimpleCell::Progress(p) if p.is_finite() => p.to_string(),
+        SimpleCell::Float { .. } | SimpleCell::Progress(_) => "null".to_string(),
+        SimpleCell::Bool(b) => b.to_string(),
+        cell => match cell.to_text() {
+            Some(text) => json_string(&text),
+            None => "null".to_string(),
+        },
+    }
+}
+
+/// Quote and escape a string as a JSON string literal.
+pub(crate) fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// GitHub flavored Markdown table.
+#[derive(Default)]
+pub struct MarkdownExporter;
+impl MarkdownExporter {
+    fn write_line(out: &mut dyn Write, cells: &[String]) -> io::Result<()> {
+        let line: Vec<String> = cells
+            .iter()
+            .map(|c| c.replace('|', "\\|").replace(['\r', '\n'], "<br>"))
+            .collect();
+        writeln!(out, "| {} |", line.join(" | "))
+    }
+}
+impl Exporter for MarkdownExporter {
+    fn write_header(&mut self, out: &mut dyn Write, headers: &[String]) -> io::Result<()> {
+        Self::write_line(out, headers)?;
+        let rule: Vec<String> = headers.iter().map(|_| "---".to_string()).collect();
+        writeln!(out, "| {} |", rule.join(" | "))
+    }
+    fn write_row(
+        &mut self,
+        out: &mut dyn Write,
+        _headers: &[String],
+        cells: &[SimpleCell],
+    ) -> io::Result<()> {
+        Self::write_line(out, &cell_texts(cells))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two columns of text, with `set_cell` storing into the grid.
+    struct Grid {
+        cells: Vec<Vec<String>>,
+    }
+    impl Grid {
+        fn new(rows: &[[&str; 2]]) -> Grid {
+            Grid {
+                cells: rows
+                    .iter()
+                    .map(|r| r.iter().map(|c| c.to_string()).collect())
+                    .collect(),
+            }
+        }
+    }
+    impl SimpleModel for Grid {
+        fn row_info(&mut self) -> RowInfo {
+            RowInfo {
+                count: self.cells.len(),
+                height: RowHeight::All(20),
+            }
+        }
+        fn column_info(&mut self) -> ColumnInfo {
+            ColumnInfo {
+                details: ["Name", "Note"]
+                    .iter()
+                    .map(|h| ColumnDetail {
+                        header: h.to_string(),
+                        width: 80,
+                    })
+                    .collect(),
+            }
+        }
+        fn get_cell(&mut self, row: i32, col: i32) -> SimpleCell {
+            SimpleCell::Text(self.cells[row as usize][col as usize].clone())
+        }
+        fn is_editable(&self, _row: i32, col: i32) -> bool {
+            col == 1
+        }
+        fn set_cell(&mut self, row: i32, col: i32, value: &str) -> Result<(), String> {
+            self.cells[row as usize][col as usize] = value.to_string();
+            Ok(())
+        }
+    }
+
+    fn export_to_string(model: &mut Grid, exporter: &mut dyn Exporter) -> String {
+        let mut out = Vec::new();
+        let count = model.row_info().count;
+        export(model, 0..count, exporter, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn csv_quotes() {
+        let mut grid = Grid::new(&[["a,b", "say \"hi\""], ["plain", "two\nlines"]]);
+        assert_eq!(
+            export_to_string(&mut grid, &mut CsvExporter::default()),
+            "Name,Note\r\n\"a,b\",\"say \"\"hi\"\"\"\r\nplain,\"two\nlines\"\r\n"
+        );
+        assert_eq!(
+            export_to_string(&mut grid, &mut CsvExporter { header: false }),
+            "\"a,b\",\"say \"\"hi\"\"\"\r\nplain,\"two\nlines\"\r\n"
+        );
+    }
+
+    #[test]
+    fn tsv_replaces_separators() {
+        let mut grid = Grid::new(&[["a\tb", "c\nd"]]);
+        assert_eq!(
+            export_to_string(&mut grid, &mut TsvExporter::default()),
+            "Name\tNote\na b\tc d\n"
+        );
+    }
+
+    #[test]
+    fn json_objects() {
+        let mut grid = Grid::new(&[["a", "q\"\\"], ["b", "\u{1}"]]);
+        assert_eq!(
+            export_to_string(&mut grid, &mut JsonExporter::default()),
+            "[\n  {\"Name\":\"a\",\"Note\":\"q\\\"\\\\\"},\n  {\"Name\":\"b\",\"Note\":\"\\u0001\"}\n]\n"
+        );
+    }
+
+    #[test]
+    fn markdown_escapes() {
+        let mut grid = Grid::new(&[["a|b", "c\nd"]]);
+        assert_eq!(
+            export_to_string(&mut grid, &mut MarkdownExporter),
+            "| Name | Note |\n| --- | --- |\n| a\\|b | c<br>d |\n"
+        );
+    }
+
+    #[test]
+    fn export_selected_columns() {
+        let mut grid = Grid::new(&[["a", "b"], ["c", "d"]]);
+        let mut out = Vec::new();
+        export_cells(
+            &mut grid,
+            [1],
+            &[1, 5],
+            &mut TsvExporter::default(),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "Note\nd\n");
+    }
+
+    #[test]
+    fn paste_skips_read_only_and_out_of_range() {
+        let mut grid = Grid::new(&[["a", "b"], ["c", "d"]]);
+        grid.paste(0, 0, "w\tx\textra\ny\tz\nnew\trow").unwrap();
+        assert_eq!(grid.cells, vec![vec!["a", "x"], vec!["c", "z"]]);
+    }
+
+    #[test]
+    fn sort_keys_click() {
+        let mut keys = SortKeys::default();
+        keys.click(2, false);
+        assert_eq!(keys.keys, vec![(2, Order::Ascending)]);
+        keys.click(2, false);
+        assert_eq!(keys.keys, vec![(2, Order::Descending)]);
+        keys.click(1, false);
+        assert_eq!(keys.keys, vec![(1, Order::Ascending)]);
+        assert_eq!(keys.get(2), None);
+    }
+
+    #[test]
+    fn sort_keys_extend() {
+        let mut keys = SortKeys::default();
+        keys.click(0, false);
+        keys.click(3, true);
+        assert_eq!(
+            keys.keys,
+            vec![(0, Order::Ascending), (3, Order::Ascending)]
+        );
+        assert_eq!(keys.get(3), Some((1, Order::Ascending)));
+        keys.click(3, true);
+        assert_eq!(keys.get(3), Some((1, Order::Descending)));
+        keys.click(3, true);
+        assert_eq!(keys.keys, vec![(0, Order::Ascending)]);
+        // a plain click on one of several keys starts over
+        keys.click(1, true);
+        keys.click(1, false);
+        assert_eq!(keys.keys, vec![(1, Order::Ascending)]);
+    }
+
+    fn names() -> VecModel<(u64, String)> {
+        let items = ["b", "c", "a"]
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (i as u64, n.to_string()))
+            .collect();
+        VecModel::new(
+            items,
+            vec![VecColumn::new("Name", 80, |i: &(u64, String)| {
+                SimpleCell::Text(i.1.clone())
+            })
+            .with_edit(|i, v| {
+                i.1 = v.to_string();
+                Ok(())
+            })],
+        )
+        .with_row_key(|i| i.0)
+    }
+
+    #[test]
+    fn vec_model_sort_and_filter() {
+        let mut model = names();
+        model.sort(0, Order::Ascending);
+        assert_eq!(model.row_key(0), Some(2));
+        model.set_filter(|i| i.1 != "a");
+        assert_eq!(model.row_info().count, 2);
+        assert_eq!(model.get_cell(0, 0).as_str(), Some("b"));
+        model.set_cell(1, 0, "d").unwrap();
+        assert_eq!(model.items()[1].1, "d");
+    }
+
+    #[test]
+    fn vec_model_read_after_removing_items() {
+        let mut model = names();
+        assert_eq!(model.row_info().count, 3);
+        model.items_mut().truncate(1);
+        assert!(model.item(2).is_none());
+        assert!(model.get_cell(2, 0).as_str().is_none());
+        assert_eq!(model.row_key(2), None);
+        assert!(model.hover(2, 0).is_none());
+        assert_eq!(model.row_style(2), CellStyle::default());
+        assert!(model.set_cell(2, 0, "x").is_err());
+        assert!(!model.activate(2, 0));
+        model.items_mut().clear();
+        assert!(model.item(0).is_none());
+        assert_eq!(model.row_info().count, 0);
+    }
+
+    type Row = (i64, f64, bool, f64);
+
+    #[test]
+    fn typed_export() {
+        let date =
+            NaiveDateTime::parse_from_str("2024-05-06 07:08:09", "%Y-%m-%d %H:%M:%S").unwrap();
+        let mut model = VecModel::new(
+            vec![(7, 2.5, true, f64::NAN)],
+            vec![
+                VecColumn::new("Int", 50, |r: &Row| SimpleCell::Int(r.0)),
+                VecColumn::new("Float", 50, |r: &Row| SimpleCell::Float {
+                    value: r.1,
+                    precision: 2,
+                }),
+                VecColumn::new("Bool", 50, |r: &Row| SimpleCell::Bool(r.2)),
+                VecColumn::new("NaN", 50, |r: &Row| SimpleCell::Float {
+                    value: r.3,
+                    precision: 1,
+                }),
+                VecColumn::new("Done", 50, |_: &Row| SimpleCell::Progress(0.25)),
+                VecColumn::new("Date", 50, move |_: &Row| SimpleCell::DateTime {
+                    value: date,
+                    format: "%Y-%m-%d".to_string(),
+                }),
+                VecColumn::new("Empty", 50, |_: &Row| SimpleCell::None),
+            ],
+        );
+        model.row_info();
+        let mut out = Vec::new();
+        export(&mut model, 0..1, &mut JsonExporter::default(), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "[\n  {\"Int\":7,\"Float\":2.50,\"Bool\":true,\"NaN\":null,\"Done\":0.25,\
+             \"Date\":\"2024-05-06\",\"Empty\":null}\n]\n"
+        );
+        let mut out = Vec::new();
+        export(
+            &mut model,
+            0..1,
+            &mut CsvExporter { header: false },
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "7,2.50,true,NaN,25%,2024-05-06,\r\n"
+        );
+    }
 }
@@ -1,21 +1,40 @@
 #[cfg(feature = "hover")]
 use std::ffi::CStr;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    io::{self, Write},
+    ops::Range,
     sync::{Arc, Mutex},
 };
 
 #[cfg(feature = "hover")]
 use fltk::misc::Tooltip;
 use fltk::{
+    app::{self, MouseButton},
+    dialog,
     draw::{self},
-    enums::{self, Event, Font},
-    prelude::{TableExt, WidgetBase, WidgetExt},
-    table::{Table, TableContext},
+    enums::{self, CallbackTrigger, Damage, Event, EventState, Key},
+    input::Input,
+    prelude::{GroupExt, InputExt, TableExt, WidgetBase, WidgetExt},
+    table::{Table, TableContext, TableResizeFlag},
+    widget::Widget,
 };
 use timer::Guard;
 
-use crate::simple_model::{Order, SimpleCell, SimpleModel};
+use crate::cell_draw::draw_value;
+use crate::cell_editor::{CellEditor, EditorSlot};
+use crate::column_view::{fit_width, ColumnView};
+use crate::cursor::{is_enter, CellCursor};
+use crate::filtered_model::FilteredModel;
+use crate::header::{draw_sort_indicator, header_menu, HeaderAction};
+use crate::model_events::{subscribe_ui, ModelEvent};
+use crate::selection::{SelectionMode, SelectionModel};
+use crate::simple_model::{
+    export_cells, ColumnDetail, ExportRows, Exporter, Order, SimpleCell, SimpleModel, SortKeys,
+    TsvExporter,
+};
+use crate::theme::TableTheme;
+use crate::view_state::TableViewState;
 
 /// Define a FLTK table with a data model
 pub struct SimpleTable<T>
@@ -25,11 +44,18 @@ where
     pub table: Table,
     pub model: Arc<Mutex<T>>,
 
-    font: Font,
-    font_size: i32,
+    theme: Arc<Mutex<TableTheme>>,
+    copy_headers: Arc<Mutex<bool>>,
+    editor: EditorSlot,
+    widgets: Arc<Mutex<WidgetPool>>,
+    sort_keys: Arc<Mutex<SortKeys>>,
+    columns: Arc<Mutex<ColumnView>>,
+    cursor: Arc<Mutex<CellCursor>>,
+    selection: Arc<Mutex<SelectionModel>>,
+    quick_filter: Option<Input>,
 }
 
-fn draw_header(txt: &str, x: i32, y: i32, w: i32, h: i32) {
+fn draw_header(txt: &str, x: i32, y: i32, w: i32, h: i32, theme: &TableTheme) {
     draw::push_clip(x, y, w, h);
     draw::draw_box(
         enums::FrameType::ThinUpBox,
@@ -37,9 +63,10 @@ fn draw_header(txt: &str, x: i32, y: i32, w: i32, h: i32) {
         y,
         w,
         h,
-        enums::Color::FrameDefault,
+        theme.header_background,
     );
-    draw::set_draw_color(enums::Color::Black);
+    draw::set_draw_color(theme.header_foreground);
+    draw::set_font(theme.header_font, theme.header_font_size);
     draw::draw_text2(txt, x, y, w, h, enums::Align::Center);
     draw::pop_clip();
 }
@@ -51,40 +78,199 @@ where
     T: SimpleModel + Send + 'static,
 {
     pub fn new(mut table: Table, mut model: T) -> SimpleTable<T> {
+        let theme = Arc::new(Mutex::new(TableTheme {
+            font: enums::Font::Courier,
+            header_font: enums::Font::Courier,
+            ..Default::default()
+        }));
+        table.set_color(theme.lock().unwrap().background);
+        let columns: Arc<Mutex<ColumnView>> = Default::default();
         // initialize table
         {
-            table.set_cols(model.column_info().details.len() as i32);
+            let details = model.column_info().details;
+            let mut columns = columns.lock().unwrap();
+            columns.update(details.len());
+            table.set_cols(details.len() as i32);
             table.set_col_header(true);
-            for i in 0..model.column_info().details.len() {
-                table.set_col_width(i as i32, model.column_info().details[i].width as i32);
-            }
+            apply_col_widths(&mut table, &columns, &details);
             table.set_col_resize(true);
         }
         let model = Arc::new(Mutex::new(model));
+        let copy_headers = Arc::new(Mutex::new(false));
+        let editor: EditorSlot = Default::default();
+        let widgets: Arc<Mutex<WidgetPool>> = Default::default();
+        let sort_keys: Arc<Mutex<SortKeys>> = Default::default();
+        let cursor: Arc<Mutex<CellCursor>> = Default::default();
+        let selection: Arc<Mutex<SelectionModel>> = Default::default();
         {
             let model = model.clone();
-            let mut old_sort_col = -1;
-            let mut sort_order = Order::Ascending;
+            let copy_headers = copy_headers.clone();
+            let editor = editor.clone();
+            let widgets = widgets.clone();
+            let sort_keys = sort_keys.clone();
+            let columns = columns.clone();
+            let theme = theme.clone();
+            let cursor = cursor.clone();
+            let selection = selection.clone();
+            // header being dragged, and whether it has moved
+            let mut drag_col: Option<(i32, bool)> = None;
+            let mut resizing = false;
+            // cells being selected by dragging
+            let mut selecting = false;
             #[cfg(feature = "hover")]
             let tooltip_cell = (-1, -1);
             table.handle(move |t, ev: Event| {
                 match ev {
-                    Event::Push => {
-                        // handle sorting
-                        if let Some((TableContext::ColHeader, _row, col, _)) = t.cursor2rowcol() {
-                            if col != old_sort_col {
-                                sort_order = Order::Ascending;
-                                old_sort_col = col;
-                            } else {
-                                sort_order = sort_order.next();
+                    // let widgets in cells handle their own clicks
+                    Event::Push
+                        if CellEditor::event_inside(&editor)
+                            || widgets.lock().unwrap().event_inside() =>
+                    {
+                        false
+                    }
+                    Event::Push if app::event_mouse_button() == MouseButton::Right => {
+                        match t.cursor2rowcol() {
+                            Some((TableContext::ColHeader, _row, col, _)) => {
+                                let theme = *theme.lock().unwrap();
+                                header_menu_action(
+                                    t, &model, &columns, &sort_keys, &selection, &cursor, col,
+                                    &theme,
+                                );
+                                true
+                            }
+                            _ => false,
+                        }
+                    }
+                    Event::Push => match t.cursor2rowcol() {
+                        // double click a border to fit the column
+                        Some((TableContext::ColHeader, _row, col, flag))
+                            if flag != TableResizeFlag::None && app::event_clicks() =>
+                        {
+                            let col = match flag {
+                                TableResizeFlag::ColLeft => col - 1,
+                                _ => col,
+                            };
+                            if col >= 0 {
+                                let mut model = model.lock().unwrap();
+                                let mut columns = columns.lock().unwrap();
+                                let theme = theme.lock().unwrap();
+                                autofit(t, &mut *model, &mut columns, col, &theme);
                             }
-                            model.lock().unwrap().sort(col as usize, sort_order);
-                            t.damage();
                             true
-                        } else {
+                        }
+                        // let the table resize, then keep the widths on release
+                        Some((TableContext::ColHeader, _row, _col, flag))
+                            if flag != TableResizeFlag::None =>
+                        {
+                            resizing = true;
                             false
                         }
+                        // sort or move the column on release, unless resizing
+                        Some((TableContext::ColHeader, _row, col, TableResizeFlag::None)) => {
+                            drag_col = Some((col, false));
+                            true
+                        }
+                        // double click to edit
+                        Some((TableContext::Cell, row, col, _)) if app::event_clicks() => {
+                            cursor.lock().unwrap().set(row, col);
+                            edit_cell(t, &model, &editor, &columns, row, col)
+                        }
+                        Some((TableContext::Cell, row, col, _)) => {
+                            // e.g. collapse a group
+                            let model_col = columns.lock().unwrap().model_col(col as usize);
+                            let clicked = model_col.is_some_and(|model_col| {
+                                model.lock().unwrap().click(row, model_col as i32)
+                            });
+                            if clicked {
+                                refresh(t, &model, &columns, &selection, &cursor);
+                                SelectionModel::notify(&selection);
+                                t.redraw();
+                                return true;
+                            }
+                            let state = app::event_state();
+                            cursor.lock().unwrap().set(row, col);
+                            selection.lock().unwrap().click(
+                                row as usize,
+                                col as usize,
+                                state.contains(EventState::Command),
+                                state.contains(EventState::Shift),
+                            );
+                            SelectionModel::notify(&selection);
+                            selecting = true;
+                            let _ = t.take_focus();
+                            t.redraw();
+                            true
+                        }
+                        // select the whole row
+                        Some((TableContext::RowHeader, row, _col, _)) => {
+                            let shift = app::event_state().contains(EventState::Shift);
+                            {
+                                let mut cursor = cursor.lock().unwrap();
+                                let col = cursor.col;
+                                cursor.set(row, col);
+                            }
+                            selection.lock().unwrap().click_row(row as usize, shift);
+                            SelectionModel::notify(&selection);
+                            let _ = t.take_focus();
+                            t.redraw();
+                            true
+                        }
+                        _ => false,
+                    },
+                    Event::Drag => match drag_col.as_mut() {
+                        Some((_, moved)) => {
+                            *moved = true;
+                            true
+                        }
+                        None if selecting => {
+                            if let Some((TableContext::Cell, row, col, _)) = t.cursor2rowcol() {
+                                cursor.lock().unwrap().set(row, col);
+                                selection
+                                    .lock()
+                                    .unwrap()
+                                    .extend_to(row as usize, col as usize);
+                                SelectionModel::notify(&selection);
+                                t.redraw();
+                            }
+                            true
+                        }
+                        None => false,
+                    },
+                    Event::Released if selecting => {
+                        selecting = false;
+                        true
                     }
+                    Event::Released if resizing => {
+                        resizing = false;
+                        let details = model.lock().unwrap().column_info().details;
+                        record_col_widths(t, &mut columns.lock().unwrap(), &details);
+                        false
+                    }
+                    Event::Released => match drag_col.take() {
+                        Some((from, true)) => {
+                            if let Some((_, _, to, _)) = t.cursor2rowcol() {
+                                if to != from {
+                                    let details = model.lock().unwrap().column_info().details;
+                                    let mut columns = columns.lock().unwrap();
+                                    columns.move_col(from as usize, to as usize);
+                                    apply_col_widths(t, &columns, &details);
+                                    t.redraw();
+                                }
+                            }
+                            true
+                        }
+                        Some((col, false)) => {
+                            let Some(col) = columns.lock().unwrap().model_col(col as usize) else {
+                                return false;
+                            };
+                            let mut sort_keys = sort_keys.lock().unwrap();
+                            let extend = app::event_state().contains(EventState::Shift);
+                            sort_keys.click(col, extend);
+                            sort_rows(t, &model, &selection, &cursor, &sort_keys.keys);
+                            true
+                        }
+                        None => false,
+                    },
                     #[cfg(feature = "hover")]
                     Event::Move => {
                         // handle dynamic tooltip
@@ -93,7 +279,9 @@ where
                             if (row, col) != tooltip_cell {
                                 tooltip_cell = (row, col);
 
-                                let hover = model.lock().unwrap().hover(row, col);
+                                let col = columns.lock().unwrap().model_col(col as usize);
+                                let hover =
+                                    col.and_then(|c| model.lock().unwrap().hover(row, c as i32));
                                 if let Some(my_string) = hover {
                                     Tooltip::enable(true);
                                     // Copy char* into global.  FLTK hover uses a static CStr.
@@ -112,6 +300,79 @@ where
                         }
                         false
                     }
+                    // accept keyboard focus, and show or hide the focus rectangle
+                    Event::Focus | Event::Unfocus => {
+                        t.redraw();
+                        true
+                    }
+                    Event::KeyDown if app::event_key() == Key::F2 => {
+                        let cursor = *cursor.lock().unwrap();
+                        edit_cell(t, &model, &editor, &columns, cursor.row, cursor.col)
+                    }
+                    Event::KeyDown if is_enter(app::event_key()) => {
+                        let cursor = *cursor.lock().unwrap();
+                        activate(t, &model, &editor, &columns, cursor.row, cursor.col)
+                    }
+                    // expand or collapse a tree row
+                    Event::KeyDown if matches!(app::event_text().as_str(), "+" | "-") => {
+                        let row = cursor.lock().unwrap().row;
+                        let expand = app::event_text() == "+";
+                        let expanded = model.lock().unwrap().expand(row, expand);
+                        if expanded {
+                            refresh(t, &model, &columns, &selection, &cursor);
+                            SelectionModel::notify(&selection);
+                            t.redraw();
+                        }
+                        expanded
+                    }
+                    Event::KeyDown if app::event_state().contains(EventState::Command) => {
+                        match app::event_key() {
+                            k if k == Key::from_char('c') => {
+                                let headers = *copy_headers.lock().unwrap();
+                                let text = selection_text(t, &selection, &model, &columns, headers);
+                                app::copy(&text);
+                                true
+                            }
+                            k if k == Key::from_char('v') => {
+                                app::paste_text(t);
+                                true
+                            }
+                            k if k == Key::from_char('a') => {
+                                let (rows, cols) = (t.rows().max(0), t.cols().max(0));
+                                selection
+                                    .lock()
+                                    .unwrap()
+                                    .select_all(rows as usize, cols as usize);
+                                SelectionModel::notify(&selection);
+                                t.redraw();
+                                true
+                            }
+                            _ => false,
+                        }
+                    }
+                    Event::KeyDown => {
+                        let page = match t.try_visible_cells() {
+                            Some((top, bottom, _, _)) => bottom - top,
+                            None => 1,
+                        };
+                        let mut cursor = cursor.lock().unwrap();
+                        if !cursor.navigate(app::event_key(), t.rows(), t.cols(), page) {
+                            return false;
+                        }
+                        let (row, col) = (cursor.row as usize, cursor.col as usize);
+                        if app::event_state().contains(EventState::Shift) {
+                            selection.lock().unwrap().extend_to(row, col);
+                        } else {
+                            selection.lock().unwrap().click(row, col, false, false);
+                        }
+                        SelectionModel::notify(&selection);
+                        show_cursor(t, &cursor);
+                        true
+                    }
+                    Event::Paste => {
+                        paste(t, &model, &columns, &selection, &app::event_text());
+                        true
+                    }
                     /* other events to be handled */
                     _ => false,
                 }
@@ -119,14 +380,25 @@ where
         }
         let mut simple_table = SimpleTable {
             table,
-            font: enums::Font::Courier,
-            font_size: 12,
+            theme,
             model,
+            copy_headers,
+            editor,
+            widgets,
+            sort_keys,
+            columns,
+            cursor,
+            selection,
+            quick_filter: None,
         };
         {
             let model = simple_table.model.clone();
-            let font = simple_table.font;
-            let font_size = simple_table.font_size;
+            let theme = simple_table.theme.clone();
+            let widgets = simple_table.widgets.clone();
+            let sort_keys = simple_table.sort_keys.clone();
+            let columns = simple_table.columns.clone();
+            let cursor = simple_table.cursor.clone();
+            let selection = simple_table.selection.clone();
             let mut row_heights: HashMap<i32, i32> = HashMap::new();
             simple_table.table.draw_cell(
                 move |t: &mut Table,
@@ -137,53 +409,99 @@ where
                       y: i32,
                       w: i32,
                       h: i32| {
+                    let theme = *theme.lock().unwrap();
                     match ctx {
-                        TableContext::StartPage => draw::set_font(font, font_size),
+                        TableContext::StartPage => {
+                            widgets.lock().unwrap().start_page();
+                            draw::set_font(theme.font, theme.font_size)
+                        }
                         TableContext::ColHeader => {
+                            let Some(col) = columns.lock().unwrap().model_col(col as usize) else {
+                                return;
+                            };
                             let column_info = model.lock().unwrap().column_info();
-                            let txt = column_info.details[col as usize].header.as_str();
-                            draw_header(txt, x, y, w, h)
+                            let txt = column_info.details[col].header.as_str();
+                            draw_header(txt, x, y, w, h, &theme);
+                            let sort_keys = sort_keys.lock().unwrap();
+                            let color = theme.header_foreground;
+                            draw_sort_indicator(&sort_keys, col, (x, y, w, h), color);
+                        }
+                        TableContext::RowHeader => {
+                            let label = model.lock().unwrap().row_header(row);
+                            draw_header(label.as_deref().unwrap_or_default(), x, y, w, h, &theme);
                         }
-                        //TableContext::RowHeader => J1939Table::draw_header(&format!("{}", row + 1), x, y, w, h), // Row titles
-                        TableContext::RowHeader => {}
                         TableContext::Cell => {
+                            let Some(model_col) = columns.lock().unwrap().model_col(col as usize)
+                            else {
+                                return;
+                            };
+                            let model_col = model_col as i32;
                             draw::push_clip(x, y, w, h);
-                            let selected = t.is_selected(row, col);
-                            // FIXME use L&F
+                            let selected = selection
+                                .lock()
+                                .unwrap()
+                                .is_selected(row as usize, col as usize);
+                            let (cell, style) = {
+                                let mut model = model.lock().unwrap();
+                                let style =
+                                    model.row_style(row).merge(model.cell_style(row, model_col));
+                                (model.get_cell(row, model_col), style)
+                            };
                             if selected {
-                                draw::set_draw_color(enums::Color::from_u32(0x00D3_D3D3));
+                                draw::set_draw_color(theme.selection_background);
                             } else {
-                                draw::set_draw_color(enums::Color::White);
+                                draw::set_draw_color(style.bg.unwrap_or(theme.row_background(row)));
                             }
                             draw::draw_rectf(x, y, w, h);
-                            match model.lock().unwrap().get_cell(row, col) {
+                            match cell {
                                 SimpleCell::Delegate(dd) => {
-                                    dd.draw(row, col, x, y, w, h, t.is_selected(row, col));
+                                    dd.draw(row, model_col, x, y, w, h, selected);
                                 }
-                                SimpleCell::Text(value) => {
-                                    let str = value.as_str();
-                                    let calc_height = (4 + draw::height())
-                                        * (1 + str.matches("\n").count() as i32);
-                                    update_min_height(&mut row_heights, row, calc_height, t);
-                                    draw::set_draw_color(enums::Color::Gray0);
-                                    draw::draw_text2(
-                                        str,
-                                        x + 2,
-                                        y + 2,
-                                        w - 4,
-                                        h - 4,
-                                        enums::Align::Left,
+                                SimpleCell::Widget(widget) => {
+                                    let mut widgets = widgets.lock().unwrap();
+                                    widgets.place(t, row, col, widget, x, y, w, h);
+                                }
+                                SimpleCell::None => {}
+                                cell => {
+                                    let padding = style.padding.unwrap_or(2);
+                                    draw::set_font(
+                                        style.font_or(theme.font),
+                                        style.size.unwrap_or(theme.font_size),
+                                    );
+                                    if let Some(str) = cell.as_str() {
+                                        let calc_height = (2 * padding + draw::height())
+                                            * (1 + str.matches("\n").count() as i32);
+                                        update_min_height(&mut row_heights, row, calc_height, t);
+                                    }
+                                    let color = if selected {
+                                        theme.selected_foreground()
+                                    } else {
+                                        style.fg.unwrap_or(theme.foreground)
+                                    };
+                                    draw_value(
+                                        &cell,
+                                        (
+                                            x + padding,
+                                            y + padding,
+                                            w - 2 * padding,
+                                            h - 2 * padding,
+                                        ),
+                                        style.align,
+                                        color,
+                                        &theme,
                                     );
                                 }
-                                SimpleCell::Widget(widget) => todo!(),
-                                SimpleCell::None => todo!(),
                             }
-                            draw::set_draw_color(enums::Color::Light3);
+                            draw::set_draw_color(theme.grid);
                             draw::draw_rect(x, y, w, h);
+                            if t.has_focus() && cursor.lock().unwrap().is_at(row, col) {
+                                draw::set_draw_color(theme.focus);
+                                draw::draw_focus_rect(x + 1, y + 1, w - 2, h - 2);
+                            }
                             draw::pop_clip();
                         }
                         TableContext::None => {}
-                        TableContext::EndPage => {}
+                        TableContext::EndPage => widgets.lock().unwrap().end_page(t),
                         TableContext::Table => {}
                         TableContext::RcResize => {
                             row_heights.clear();
@@ -197,25 +515,38 @@ where
     }
 
     pub fn reset(&mut self) {
-        self.table.clear();
+        CellEditor::close(&self.editor);
+        self.widgets.lock().unwrap().cells.clear();
+        TableExt::clear(&mut self.table);
         self.redraw();
     }
     pub fn set_font(&mut self, font: enums::Font, size: i32) {
-        self.font = font;
-        self.font_size = size;
+        let mut theme = self.theme.lock().unwrap();
+        theme.font = font;
+        theme.font_size = size;
+        self.table.redraw();
+    }
+
+    pub fn theme(&self) -> TableTheme {
+        *self.theme.lock().unwrap()
+    }
+
+    pub fn set_theme(&mut self, theme: TableTheme) {
+        *self.theme.lock().unwrap() = theme;
+        self.table.set_color(theme.background);
+        self.table.redraw();
     }
 
     // Mark for redraw immediately.
     pub fn redraw(&mut self) {
-        let (row_count, col_count) = {
-            let mut simple_model = self.model.lock().unwrap();
-            (
-                simple_model.row_info().count,
-                simple_model.column_info().details.len(),
-            )
-        };
-        self.table.set_rows(row_count as i32);
-        self.table.set_cols(col_count as i32);
+        refresh(
+            &mut self.table,
+            &self.model,
+            &self.columns,
+            &self.selection,
+            &self.cursor,
+        );
+        SelectionModel::notify(&self.selection);
         self.table.set_damage(true); // FIXME verify that it's required
         fltk::app::awake();
     }
@@ -224,6 +555,9 @@ where
     /// The Timer is passed in, so multiple events can share the timer.
     pub fn redraw_on(&mut self, timer: &timer::Timer, duration: chrono::Duration) {
         let model = self.model.clone();
+        let columns = self.columns.clone();
+        let selection = self.selection.clone();
+        let cursor = self.cursor.clone();
         let table = Arc::new(Mutex::new(self.table.clone()));
         let guard: Arc<Mutex<Option<Guard>>> = Arc::new(Mutex::new(None));
         guard
@@ -233,21 +567,9 @@ where
             .replace(timer.schedule_repeating(duration, move || {
                 let mut table = table.lock().unwrap();
                 if table.visible_r() {
-                    // FIXME why can't this call be made?
-                    //self.redraw();
-                    {
-                        let (rc, cc) = {
-                            let mut simple_model = model.lock().unwrap();
-                            (
-                                simple_model.row_info().count,
-                                simple_model.column_info().details.len(),
-                            )
-                        };
-                        table.set_rows(rc as i32);
-                        table.set_cols(cc as i32);
-                        // table.set_damage(true); // FIXME verify that it's requiredS
-                        fltk::app::awake();
-                    };
+                    refresh(&mut table, &model, &columns, &selection, &cursor);
+                    SelectionModel::notify_ui(&selection);
+                    fltk::app::awake();
                 } else {
                     // No longer visible, so stop timer
                     guard.lock().unwrap().take();
@@ -255,12 +577,295 @@ where
             }));
     }
 
+    /// Redraw when the model reports changes with `SimpleModel::events`.  Updated rows are repainted, and
+    /// other changes refresh the whole table.  Returns false if the model doesn't report changes, so
+    /// `redraw_on` is needed instead.
+    pub fn redraw_on_events(&mut self) -> bool {
+        let Some(events) = self.model.lock().unwrap().events() else {
+            return false;
+        };
+        let mut table = self.table.clone();
+        let model = self.model.clone();
+        let columns = self.columns.clone();
+        let selection = self.selection.clone();
+        let cursor = self.cursor.clone();
+        subscribe_ui(&events, move |events| {
+            if table.was_deleted() {
+                return;
+            }
+            if events.iter().all(ModelEvent::is_update) {
+                for event in events {
+                    if let ModelEvent::RowsUpdated(rows) = event {
+                        damage_rows(&mut table, rows);
+                    }
+                }
+            } else {
+                refresh(&mut table, &model, &columns, &selection, &cursor);
+                SelectionModel::notify(&selection);
+                table.redraw();
+            }
+        });
+        true
+    }
+
+    /// Export the selected, visible or all rows.
+    pub fn export(
+        &self,
+        rows: ExportRows,
+        exporter: &mut dyn Exporter,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        let columns = self.columns.lock().unwrap();
+        let count = self.model.lock().unwrap().row_info().count;
+        let (rows, cols): (Box<dyn Iterator<Item = usize>>, Vec<usize>) = match rows {
+            ExportRows::All => (Box::new(0..count), columns.displayed().to_vec()),
+            ExportRows::Selection => {
+                let selection = self.selection.lock().unwrap();
+                let cols = selection.selected_cols(columns.displayed().len());
+                let cols = cols
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|c| columns.model_col(c));
+                let rows = selection.selected_rows(count).into_iter().flatten();
+                (Box::new(rows), cols.collect())
+            }
+            ExportRows::Visible => match self.table.try_visible_cells() {
+                Some((top, bottom, _, _)) if top >= 0 => (
+                    Box::new(top as usize..bottom as usize + 1),
+                    columns.displayed().to_vec(),
+                ),
+                _ => (Box::new(0..0), vec![]),
+            },
+        };
+        export_cells(&mut *self.model.lock().unwrap(), rows, &cols, exporter, out)
+    }
+
+    /// Model columns in display order, including hidden columns.
+    pub fn column_order(&self) -> Vec<usize> {
+        self.columns.lock().unwrap().order().to_vec()
+    }
+
+    /// Display the model columns in this order.  Columns missing from the order are displayed last.
+    pub fn set_column_order(&mut self, order: Vec<usize>) {
+        self.columns.lock().unwrap().set_order(order);
+        self.apply_view();
+    }
+
+    pub fn is_column_hidden(&self, col: usize) -> bool {
+        self.columns.lock().unwrap().is_hidden(col)
+    }
+
+    /// Hide or show a model column.  The last displayed column can't be hidden.
+    pub fn set_column_hidden(&mut self, col: usize, hidden: bool) {
+        self.columns.lock().unwrap().set_hidden(col, hidden);
+        self.apply_view();
+    }
+
+    /// Show every column in model order, at the model's widths.
+    pub fn reset_columns(&mut self) {
+        self.columns.lock().unwrap().reset();
+        self.apply_view();
+    }
+
+    /// Current width of a model column.
+    pub fn column_width(&self, col: usize) -> i32 {
+        let default = self.model.lock().unwrap().column_info().details[col].width;
+        self.columns.lock().unwrap().width(col, default) as i32
+    }
+
+    /// Override the model's width for a column.
+    pub fn set_column_width(&mut self, col: usize, width: i32) {
+        self.columns
+            .lock()
+            .unwrap()
+            .set_width(col, width.max(0) as u32);
+        self.apply_view();
+    }
+
+    /// Fit a column to its header and the visible rows.
+    pub fn autofit_column(&mut self, col: usize) {
+        let mut columns = self.columns.lock().unwrap();
+        if let Some(display_col) = columns.display_col(col) {
+            autofit(
+                &mut self.table,
+                &mut *self.model.lock().unwrap(),
+                &mut columns,
+                display_col as i32,
+                &self.theme.lock().unwrap(),
+            );
+        }
+    }
+
+    /// Go back to the model's column widths.
+    pub fn reset_column_widths(&mut self) {
+        self.columns.lock().unwrap().clear_widths();
+        self.apply_view();
+    }
+
+    fn apply_view(&mut self) {
+        let details = self.model.lock().unwrap().column_info().details;
+        apply_view(&mut self.table, &mut self.columns.lock().unwrap(), &details);
+    }
+
+    /// Current sort keys, highest priority first.
+    pub fn sort_keys(&self) -> Vec<(usize, Order)> {
+        self.sort_keys.lock().unwrap().keys.clone()
+    }
+
+    /// Sort the model, and show the keys in the header.
+    pub fn set_sort_keys(&mut self, keys: Vec<(usize, Order)>) {
+        sort_rows(
+            &mut self.table,
+            &self.model,
+            &self.selection,
+            &self.cursor,
+            &keys,
+        );
+        self.sort_keys.lock().unwrap().keys = keys;
+    }
+
+    /// Capture the user's adjustments, to be restored later with `restore_state`.
+    pub fn save_state(&self) -> TableViewState {
+        let details = self.model.lock().unwrap().column_info().details;
+        let columns = self.columns.lock().unwrap();
+        let mut state = TableViewState::default();
+        state.save_columns(&columns, &details, &self.sort_keys.lock().unwrap().keys);
+        if let Some(input) = &self.quick_filter {
+            state.filter = input.value();
+        }
+        state.selection = self
+            .selection
+            .lock()
+            .unwrap()
+            .selected_rows(self.table.rows().max(0) as usize);
+        state.top_row = self.table.row_position().max(0) as usize;
+        let left = self.table.col_position();
+        if left >= 0 && left < self.table.cols() {
+            let model_col = columns.model_col(left as usize);
+            state.left_col = model_col
+                .and_then(|c| details.get(c))
+                .map(|d| d.header.clone());
+        }
+        state
+    }
+
+    /// Apply a saved state.  Columns the model no longer has are ignored, and new columns are displayed last.
+    /// The selection is restored as whole rows.
+    pub fn restore_state(&mut self, state: &TableViewState) {
+        let details = self.model.lock().unwrap().column_info().details;
+        let keys = {
+            let mut columns = self.columns.lock().unwrap();
+            let keys = state.restore_columns(&mut columns, &details);
+            apply_view(&mut self.table, &mut columns, &details);
+            keys
+        };
+        self.set_sort_keys(keys);
+        if let Some(input) = &mut self.quick_filter {
+            input.set_value(&state.filter);
+            input.do_callback();
+        }
+        self.redraw();
+        self.selection
+            .lock()
+            .unwrap()
+            .select_row_ranges(&state.selection);
+        SelectionModel::notify(&self.selection);
+        self.table.redraw();
+        self.table.set_row_position(state.top_row as i32);
+        let left_col = state.left_col(&details);
+        if let Some(c) = left_col.and_then(|c| self.columns.lock().unwrap().display_col(c)) {
+            self.table.set_col_position(c as i32);
+        }
+    }
+
+    /// Open the cell editor, if the cell is editable.  The column is a model column.
+    pub fn edit_cell(&mut self, row: i32, col: i32) -> bool {
+        let display_col = self.columns.lock().unwrap().display_col(col as usize);
+        match display_col {
+            Some(c) => edit_cell(
+                &mut self.table,
+                &self.model,
+                &self.editor,
+                &self.columns,
+                row,
+                c as i32,
+            ),
+            None => false,
+        }
+    }
+
+    /// The current selection.
+    pub fn selection(&self) -> SelectionModel {
+        self.selection.lock().unwrap().clone()
+    }
+
+    /// Whether clicks select rows, cells or columns.  Clears the selection.
+    pub fn set_selection_mode(&mut self, mode: SelectionMode) {
+        self.selection.lock().unwrap().set_mode(mode);
+        SelectionModel::notify(&self.selection);
+        self.table.redraw();
+    }
+
+    pub fn select_rows(&mut self, rows: Range<usize>) {
+        self.selection.lock().unwrap().select_rows(rows);
+        SelectionModel::notify(&self.selection);
+        self.table.redraw();
+    }
+
+    /// Called with the new selection whenever the user changes it.
+    pub fn on_selection_changed(&mut self, listener: impl FnMut(&SelectionModel) + Send + 'static) {
+        self.selection
+            .lock()
+            .unwrap()
+            .on_selection_changed(listener);
+    }
+
+    /// Show the row header, labeled by `SimpleModel::row_header`.  Clicking a row header selects the row.
+    pub fn set_row_header(&mut self, show: bool) {
+        self.table.set_row_header(show);
+        self.table.redraw();
+    }
+
+    /// Include the column headers when copying to the clipboard.
+    pub fn set_copy_headers(&mut self, headers: bool) {
+        *self.copy_headers.lock().unwrap() = headers;
+    }
+
+    /// Copy the selected cells to the clipboard as tab separated values.
+    pub fn copy_selection(&self) {
+        let headers = *self.copy_headers.lock().unwrap();
+        let text = selection_text(
+            &self.table,
+            &self.selection,
+            &self.model,
+            &self.columns,
+            headers,
+        );
+        app::copy(&text);
+    }
+
+    /// Paste tab separated values at the top left of the selection.
+    pub fn paste(&mut self, text: &str) {
+        paste(
+            &mut self.table,
+            &self.model,
+            &self.columns,
+            &self.selection,
+            text,
+        );
+    }
+
+    /// Text of every row, in the displayed columns.
     pub fn copy(&self, col_delimiter: &str, row_delimier: &str) -> String {
+        let displayed = self.columns.lock().unwrap().displayed().to_vec();
         let model = &mut self.model.lock().unwrap();
         let mut str = String::new();
         for row in 0..(model.row_info().count as i32) {
-            for col in 0..(model.column_info().details.len() as i32) {
-                let c = model.get_cell(row, col).as_str().unwrap_or("").to_string();
+            for col in &displayed {
+                let c = model
+                    .get_cell(row, *col as i32)
+                    .to_text()
+                    .unwrap_or_default();
                 str.push_str(&c);
 
                 str.push_str(col_delimiter);
@@ -271,6 +876,35 @@ where
     }
 }
 
+impl<M> SimpleTable<FilteredModel<M>>
+where
+    M: SimpleModel + Send + 'static,
+{
+    /// Filter the rows as text is typed into the input.
+    pub fn set_quick_filter(&mut self, input: &mut Input) {
+        self.quick_filter = Some(input.clone());
+        let model = self.model.clone();
+        let selection = self.selection.clone();
+        let cursor = self.cursor.clone();
+        let mut table = self.table.clone();
+        input.set_trigger(CallbackTrigger::Changed);
+        input.set_callback(move |input| {
+            let count = {
+                let mut model = model.lock().unwrap();
+                model.set_text(&input.value());
+                model.row_info().count
+            };
+            // previously selected rows are no longer valid
+            selection.lock().unwrap().clear();
+            *cursor.lock().unwrap() = Default::default();
+            SelectionModel::notify(&selection);
+            table.set_rows(count as i32);
+            table.set_row_position(0);
+            table.redraw();
+        });
+    }
+}
+
 fn update_min_height(
     row_heights: &mut HashMap<i32, i32>,
     row: i32,
@@ -285,3 +919,378 @@ fn update_min_height(
         row_heights.insert(row, calc_height);
     }
 }
+
+/// Widgets returned by the model stay in the table while their cell is visible,
+/// so that they receive events like any other child widget.
+#[derive(Default)]
+struct WidgetPool {
+    cells: HashMap<(i32, i32), Widget>,
+    drawn: HashSet<(i32, i32)>,
+}
+
+impl WidgetPool {
+    fn start_page(&mut self) {
+        self.drawn.clear();
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn place(
+        &mut self,
+        table: &mut Table,
+        row: i32,
+        col: i32,
+        mut widget: Widget,
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+    ) {
+        if let Some(old) = self.cells.get(&(row, col)) {
+            if old.as_widget_ptr() != widget.as_widget_ptr() {
+                table.remove(old);
+            }
+        }
+        widget.resize(x, y, w, h);
+        if table.find(&widget) >= table.children() {
+            table.add(&widget);
+        }
+        table.draw_child(&mut widget);
+        self.drawn.insert((row, col));
+        self.cells.insert((row, col), widget);
+    }
+
+    /// Remove widgets that scrolled out of view
+    fn end_page(&mut self, table: &mut Table) {
+        let drawn = &self.drawn;
+        self.cells.retain(|cell, widget| {
+            if !drawn.contains(cell) {
+                table.remove(widget);
+            }
+            drawn.contains(cell)
+        });
+    }
+
+    fn event_inside(&self) -> bool {
+        self.cells.values().any(app::event_inside_widget)
+    }
+}
+
+/// Keep up with the model's and the view's column count.  FLTK gives new columns a default width,
+/// so widths are reapplied from the view when the count changes.
+fn set_cols(table: &mut Table, columns: &mut ColumnView, details: &[ColumnDetail]) {
+    columns.update(details.len());
+    let count = columns.displayed().len() as i32;
+    if table.cols() != count {
+        table.set_cols(count);
+        apply_col_widths(table, columns, details);
+    }
+}
+
+/// Lay out the table's columns after the view changes.
+fn apply_view(table: &mut Table, columns: &mut ColumnView, details: &[ColumnDetail]) {
+    set_cols(table, columns, details);
+    apply_col_widths(table, columns, details);
+    table.redraw();
+}
+
+/// Size the table's columns from the view.
+fn apply_col_widths(table: &mut Table, columns: &ColumnView, details: &[ColumnDetail]) {
+    for (c, model_col) in columns.displayed().iter().enumerate() {
+        let width = columns.width(*model_col, details[*model_col].width) as i32;
+        if table.col_width(c as i32) != width {
+            table.set_col_width(c as i32, width);
+        }
+    }
+}
+
+/// Show the header context menu for a displayed column, and apply the chosen action.
+#[allow(clippy::too_many_arguments)]
+fn header_menu_action<T: SimpleModel + ?Sized>(
+    table: &mut Table,
+    model: &Arc<Mutex<T>>,
+    columns: &Arc<Mutex<ColumnView>>,
+    sort_keys: &Arc<Mutex<SortKeys>>,
+    selection: &Arc<Mutex<SelectionModel>>,
+    cursor: &Arc<Mutex<CellCursor>>,
+    col: i32,
+    theme: &TableTheme,
+) {
+    let details = model.lock().unwrap().column_info().details;
+    let view = columns.lock().unwrap().clone();
+    let Some(model_col) = view.model_col(col as usize) else {
+        return;
+    };
+    match header_menu(&details, &view) {
+        Some(HeaderAction::Sort(order)) => {
+            let mut sort_keys = sort_keys.lock().unwrap();
+            sort_keys.keys = vec![(model_col, order)];
+            sort_rows(table, model, selection, cursor, &sort_keys.keys);
+        }
+        Some(HeaderAction::Autofit) => {
+            let mut model = model.lock().unwrap();
+            let mut columns = columns.lock().unwrap();
+            autofit(table, &mut *model, &mut columns, col, theme);
+        }
+        Some(HeaderAction::Reset) => {
+            let mut columns = columns.lock().unwrap();
+            columns.reset();
+            apply_view(table, &mut columns, &details);
+        }
+        Some(HeaderAction::Toggle(c)) => {
+            let mut columns = columns.lock().unwrap();
+            let hidden = columns.is_hidden(c);
+            columns.set_hidden(c, !hidden);
+            apply_view(table, &mut columns, &details);
+        }
+        None => {}
+    }
+}
+
+/// Keep widths the user changed by dragging column borders.
+fn record_col_widths(table: &Table, columns: &mut ColumnView, details: &[ColumnDetail]) {
+    for c in 0..table.cols() {
+        let Some(model_col) = columns.model_col(c as usize) else {
+            break;
+        };
+        let width = table.col_width(c);
+        if width != columns.width(model_col, details[model_col].width) as i32 {
+            columns.set_width(model_col, width as u32);
+        }
+    }
+}
+
+/// Fit a displayed column to its header and the visible rows.
+fn autofit<T: SimpleModel + ?Sized>(
+    table: &mut Table,
+    model: &mut T,
+    columns: &mut ColumnView,
+    col: i32,
+    theme: &TableTheme,
+) {
+    let rows = match table.try_visible_cells() {
+        Some((top, bottom, _, _)) if top >= 0 => top as usize..bottom as usize + 1,
+        _ => 0..0,
+    };
+    let Some(model_col) = columns.model_col(col as usize) else {
+        return;
+    };
+    let (old_font, old_size) = (draw::font(), draw::size());
+    draw::set_font(theme.font, theme.font_size);
+    let width = fit_width(model, model_col, rows);
+    draw::set_font(old_font, old_size);
+    columns.set_width(model_col, width);
+    table.set_col_width(col, width as i32);
+    table.redraw();
+}
+
+/// Open the editor for a displayed cell.
+fn edit_cell<T: SimpleModel + 'static>(
+    table: &mut Table,
+    model: &Arc<Mutex<T>>,
+    editor: &EditorSlot,
+    columns: &Arc<Mutex<ColumnView>>,
+    row: i32,
+    col: i32,
+) -> bool {
+    let Some(model_col) = columns.lock().unwrap().model_col(col as usize) else {
+        return false;
+    };
+    let model_col = model_col as i32;
+    if !model.lock().unwrap().is_editable(row, model_col) {
+        return false;
+    }
+    match table.find_cell(TableContext::Cell, row, col) {
+        Some(rect) => {
+            let mut t = table.clone();
+            CellEditor::open(
+                editor,
+                table,
+                model.clone(),
+                row,
+                model_col,
+                rect,
+                move || {
+                    t.redraw();
+                    let _ = t.take_focus();
+                },
+            );
+            true
+        }
+        None => false,
+    }
+}
+
+/// The selected cells as tab separated values.  Disjoint selections are copied as the rows and columns
+/// that have any selected cell.  The selection is released before the model is locked, as `refresh` locks
+/// the model first.
+fn selection_text<T: SimpleModel + ?Sized>(
+    table: &Table,
+    selection: &Mutex<SelectionModel>,
+    model: &Mutex<T>,
+    columns: &Mutex<ColumnView>,
+    headers: bool,
+) -> String {
+    let mut out = Vec::new();
+    let (rows, cols) = {
+        let selection = selection.lock().unwrap();
+        let columns = columns.lock().unwrap();
+        let cols: Vec<usize> = selection
+            .selected_cols(columns.displayed().len())
+            .into_iter()
+            .flatten()
+            .filter_map(|c| columns.model_col(c))
+            .collect();
+        (selection.selected_rows(table.rows().max(0) as usize), cols)
+    };
+    if !rows.is_empty() && !cols.is_empty() {
+        let mut exporter = TsvExporter { header: headers };
+        // writing to a Vec can't fail
+        let _ = export_cells(
+            &mut *model.lock().unwrap(),
+            rows.into_iter().flatten(),
+            &cols,
+            &mut exporter,
+            &mut out,
+        );
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Catch up with the model's rows and columns, keeping the selection, cursor and top row on the same rows
+/// when the model has row keys.  Callers notify selection listeners, as this also runs on timer threads.
+fn refresh<T: SimpleModel + ?Sized>(
+    table: &mut Table,
+    model: &Arc<Mutex<T>>,
+    columns: &Arc<Mutex<ColumnView>>,
+    selection: &Arc<Mutex<SelectionModel>>,
+    cursor: &Arc<Mutex<CellCursor>>,
+) {
+    let (row_count, details, new_top) = {
+        let mut model = model.lock().unwrap();
+        let top = table.row_position().max(0) as usize;
+        let new_top =
+            selection
+                .lock()
+                .unwrap()
+                .follow_rows(&mut *model, &mut cursor.lock().unwrap(), top);
+        (model.row_info().count, model.column_info().details, new_top)
+    };
+    table.set_rows(row_count as i32);
+    set_cols(table, &mut columns.lock().unwrap(), &details);
+    if let Some(top) = new_top {
+        table.set_row_position(top as i32);
+    }
+}
+
+/// Repaint the visible cells of some rows.
+fn damage_rows(table: &mut Table, rows: Range<usize>) {
+    let Some((row_top, row_bottom, col_left, col_right)) = table.try_visible_cells() else {
+        return;
+    };
+    let first = (rows.start.min(i32::MAX as usize) as i32).max(row_top);
+    let last = (rows.end.min(i32::MAX as usize) as i32 - 1).min(row_bottom);
+    if first > last {
+        return;
+    }
+    if let (Some((x, y, _, _)), Some((right, bottom, w, h))) = (
+        table.find_cell(TableContext::Cell, first, col_left),
+        table.find_cell(TableContext::Cell, last, col_right),
+    ) {
+        table.set_damage_area(Damage::All, x, y, right + w - x, bottom + h - y);
+    }
+}
+
+/// Sort the model, keeping the selection, cursor and top row on the same rows when the model has row keys.
+fn sort_rows<T: SimpleModel + ?Sized>(
+    table: &mut Table,
+    model: &Arc<Mutex<T>>,
+    selection: &Arc<Mutex<SelectionModel>>,
+    cursor: &Arc<Mutex<CellCursor>>,
+    keys: &[(usize, Order)],
+) {
+    let top = table.row_position().max(0) as usize;
+    let new_top = {
+        let mut model = model.lock().unwrap();
+        let mut selection = selection.lock().unwrap();
+        let mut cursor = cursor.lock().unwrap();
+        selection.remember_rows(&mut *model, &cursor, top);
+        model.sort_multi(keys);
+        selection.follow_rows(&mut *model, &mut cursor, top)
+    };
+    SelectionModel::notify(selection);
+    if let Some(top) = new_top {
+        table.set_row_position(top as i32);
+    }
+    table.redraw();
+}
+
+/// Scroll the cursor into view.
+fn show_cursor(table: &mut Table, cursor: &CellCursor) {
+    if let Some((row_top, row_bottom, col_left, col_right)) = table.try_visible_cells() {
+        // the last visible row and column may be cut off
+        if cursor.row < row_top {
+            table.set_row_position(cursor.row);
+        } else if cursor.row >= row_bottom && cursor.row > row_top {
+            table.set_row_position(row_top + cursor.row - row_bottom + 1);
+        }
+        if cursor.col < col_left {
+            table.set_col_position(cursor.col);
+        } else if cursor.col >= col_right && cursor.col > col_left {
+            table.set_col_position(col_left + cursor.col - col_right + 1);
+        }
+    }
+    table.redraw();
+}
+
+/// Run the action of a displayed cell.  Widgets are clicked, and cells without an action are edited.
+fn activate<T: SimpleModel + 'static>(
+    table: &mut Table,
+    model: &Arc<Mutex<T>>,
+    editor: &EditorSlot,
+    columns: &Arc<Mutex<ColumnView>>,
+    row: i32,
+    col: i32,
+) -> bool {
+    if row < 0 || row >= table.rows() {
+        return false;
+    }
+    let Some(model_col) = columns.lock().unwrap().model_col(col as usize) else {
+        return false;
+    };
+    let model_col = model_col as i32;
+    let cell = model.lock().unwrap().get_cell(row, model_col);
+    if let SimpleCell::Widget(mut widget) = cell {
+        widget.do_callback();
+        return true;
+    }
+    if model.lock().unwrap().activate(row, model_col) {
+        // rows may have been shown or hidden, as when a tree row expands
+        let rows = model.lock().unwrap().row_info().count;
+        table.set_rows(rows as i32);
+        table.redraw();
+        return true;
+    }
+    edit_cell(table, model, editor, columns, row, col)
+}
+
+/// Paste at the top left of the selection.  The model and columns are unlocked before an error is
+/// shown, since the alert runs a nested event loop that may redraw the table.
+fn paste<T: SimpleModel + ?Sized>(
+    table: &mut Table,
+    model: &Arc<Mutex<T>>,
+    columns: &Arc<Mutex<ColumnView>>,
+    selection: &Arc<Mutex<SelectionModel>>,
+    text: &str,
+) {
+    let Some((top, left)) = selection.lock().unwrap().top_left() else {
+        return;
+    };
+    let result = columns
+        .lock()
+        .unwrap()
+        .paste(&mut *model.lock().unwrap(), top as i32, left, text);
+    if let Err(e) = result {
+        dialog::alert_default(&e);
+    }
+    table.redraw();
+}
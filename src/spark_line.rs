@@ -1,23 +1,36 @@
-use fltk::{draw::{begin_line, end_line, set_draw_color, vertex}, enums::Color};
+use fltk::draw::{begin_line, end_line, set_draw_color, vertex};
 
 use crate::simple_model::DrawDelegate;
+use crate::theme::TableTheme;
 
 pub struct SparkLine {
     pub data: Vec<f64>,
+    pub theme: TableTheme,
 }
 impl SparkLine {
     pub fn new(data: Vec<f64>) -> SparkLine {
-        SparkLine { data }
+        SparkLine {
+            data,
+            theme: Default::default(),
+        }
+    }
+
+    /// Draw with the series colors of a theme, to match the table.
+    pub fn with_theme(mut self, theme: &TableTheme) -> SparkLine {
+        self.theme = *theme;
+        self
     }
 }
 impl DrawDelegate for SparkLine {
-    fn draw(&self, row: i32, _col: i32, x: i32, y: i32, w: i32, h: i32, _selected: bool) {
+    fn draw(&self, row: i32, _col: i32, x: i32, y: i32, w: i32, h: i32, selected: bool) {
         if self.data.len() < 2 {
             return;
         }
-        let colors = [Color::Red, Color::Blue, Color::Green];
-        let color = colors[row as usize % colors.len()];
-        set_draw_color(color);
+        set_draw_color(if selected {
+            self.theme.selected_foreground()
+        } else {
+            self.theme.series_color(row)
+        });
         let mut max = self
             .data
             .iter()
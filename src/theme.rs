@@ -0,0 +1,130 @@
+use fltk::enums::{Color, Font};
+use fltk_theme::ThemeType;
+
+/// Colors and fonts used to draw tables.
+/// The default follows FLTK's color map, so tables pick up whatever `fltk_theme` color theme or
+/// widget theme has been applied.  The presets match the `fltk_theme` widget themes of the same name.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableTheme {
+    pub background: Color,
+    pub foreground: Color,
+    /// background of every other row, if rows are striped
+    pub stripe: Option<Color>,
+    pub header_background: Color,
+    pub header_foreground: Color,
+    pub grid: Color,
+    pub selection_background: Color,
+    pub selection_foreground: Color,
+    /// outline of the cell with keyboard focus
+    pub focus: Color,
+    pub font: Font,
+    pub font_size: i32,
+    pub header_font: Font,
+    pub header_font_size: i32,
+    /// line colors for charts like `SparkLine`, by row
+    pub series: [Color; 3],
+}
+
+impl Default for TableTheme {
+    fn default() -> Self {
+        TableTheme {
+            background: Color::Background2,
+            foreground: Color::Foreground,
+            stripe: None,
+            header_background: Color::Background,
+            header_foreground: Color::Foreground,
+            grid: Color::Inactive,
+            selection_background: Color::Selection,
+            selection_foreground: Color::Foreground,
+            focus: Color::Foreground,
+            font: Font::Helvetica,
+            font_size: 12,
+            header_font: Font::Helvetica,
+            header_font_size: 12,
+            series: [Color::Red, Color::Blue, Color::Green],
+        }
+    }
+}
+
+impl TableTheme {
+    /// Black on white, matching the classic widget theme.
+    pub fn light() -> TableTheme {
+        TableTheme {
+            background: Color::White,
+            foreground: Color::Black,
+            stripe: Some(Color::from_rgb(0xF2, 0xF2, 0xF2)),
+            header_background: Color::from_rgb(0xD4, 0xD0, 0xC8),
+            header_foreground: Color::Black,
+            grid: Color::from_rgb(0xD3, 0xD3, 0xD3),
+            selection_background: Color::from_rgb(0x0A, 0x24, 0x6A),
+            selection_foreground: Color::White,
+            focus: Color::Black,
+            ..Default::default()
+        }
+    }
+
+    /// Matches the dark widget theme.
+    pub fn dark() -> TableTheme {
+        TableTheme {
+            background: Color::from_rgb(0x3A, 0x3A, 0x3A),
+            foreground: Color::White,
+            stripe: Some(Color::from_rgb(0x44, 0x44, 0x44)),
+            header_background: Color::from_rgb(0x53, 0x53, 0x53),
+            header_foreground: Color::White,
+            grid: Color::from_rgb(0x28, 0x28, 0x28),
+            selection_background: Color::from_rgb(0xD6, 0xD6, 0xD6),
+            selection_foreground: Color::Black,
+            focus: Color::White,
+            series: [
+                Color::from_rgb(0xFF, 0x60, 0x60),
+                Color::from_rgb(0x60, 0xA0, 0xFF),
+                Color::from_rgb(0x60, 0xE0, 0x60),
+            ],
+            ..Default::default()
+        }
+    }
+
+    /// Matches the high contrast widget theme.
+    pub fn high_contrast() -> TableTheme {
+        TableTheme {
+            background: Color::from_rgb(0x00, 0x20, 0x20),
+            foreground: Color::White,
+            stripe: None,
+            header_background: Color::Black,
+            header_foreground: Color::White,
+            grid: Color::White,
+            selection_background: Color::from_rgb(0x00, 0xFF, 0xFF),
+            selection_foreground: Color::Black,
+            focus: Color::Yellow,
+            series: [Color::Yellow, Color::Cyan, Color::Magenta],
+            ..Default::default()
+        }
+    }
+
+    /// The preset for a `fltk_theme::WidgetTheme`.
+    pub fn for_theme(theme: ThemeType) -> TableTheme {
+        match theme {
+            ThemeType::Dark => TableTheme::dark(),
+            ThemeType::HighContrast => TableTheme::high_contrast(),
+            _ => TableTheme::light(),
+        }
+    }
+
+    /// Background for a row, without selection.
+    pub fn row_background(&self, row: i32) -> Color {
+        match self.stripe {
+            Some(stripe) if row % 2 == 1 => stripe,
+            _ => self.background,
+        }
+    }
+
+    /// Text color for selected cells, adjusted by FLTK if it doesn't contrast with the selection.
+    pub fn selected_foreground(&self) -> Color {
+        Color::contrast(self.selection_foreground, self.selection_background)
+    }
+
+    /// Line color for a chart in a row.
+    pub fn series_color(&self, row: i32) -> Color {
+        self.series[row.unsigned_abs() as usize % self.series.len()]
+    }
+}
@@ -0,0 +1,404 @@
+use fltk::{
+    draw::{self, draw_polygon, draw_text2, set_draw_color},
+    enums::Align,
+};
+
+use crate::model_events::ModelEvents;
+use crate::simple_model::{
+    CellStyle, ColumnInfo, DrawDelegate, RowHeight, RowInfo, SimpleCell, SimpleModel,
+};
+use crate::theme::TableTheme;
+
+/// Width of each level of indentation, and of the disclosure triangle.
+const INDENT: i32 = 16;
+
+/// Hierarchical data, such as ECU → PGN → SPN, shown one visible node per row by `TreeTableModel`.
+pub trait TreeModel {
+    /// Identifies a node.  Kept for each visible row, so it should be cheap to clone, like a path or key.
+    type Node: Clone;
+
+    fn roots(&mut self) -> Vec<Self::Node>;
+    /// Children of a node.  Only called for expanded nodes, so children can be loaded lazily.
+    fn children(&mut self, node: &Self::Node) -> Vec<Self::Node>;
+    /// Whether a node has a triangle to expand it.  Override to avoid listing children that aren't loaded.
+    fn has_children(&mut self, node: &Self::Node) -> bool {
+        !self.children(node).is_empty()
+    }
+    fn is_expanded(&self, node: &Self::Node) -> bool;
+    /// Expand a collapsed node, or collapse an expanded one.
+    fn toggle(&mut self, node: &Self::Node);
+
+    fn column_info(&mut self) -> ColumnInfo;
+    /// Cell of a node.  The first column is drawn as text after the node's indentation and triangle.
+    fn get_cell(&mut self, node: &Self::Node, col: i32) -> SimpleCell;
+
+    /// Optional identity of a node, so the selection follows it as rows above expand or collapse.
+    fn node_key(&mut self, _node: &Self::Node) -> Option<u64> {
+        None
+    }
+}
+
+type LoadFn<M> = Box<dyn FnMut(&mut M, &<M as TreeModel>::Node) + Send>;
+
+/// Visible node, in display order.
+struct TreeRow<N> {
+    node: N,
+    depth: usize,
+    has_children: bool,
+    expanded: bool,
+}
+
+/// Shows a `TreeModel` in a table, with a row for each node whose ancestors are all expanded.
+/// The + and - keys, or Enter, expand and collapse the focused row.
+pub struct TreeTableModel<M: TreeModel> {
+    pub model: M,
+    rows: Vec<TreeRow<M::Node>>,
+    dirty: bool,
+    row_height: u32,
+    theme: TableTheme,
+    loader: Option<LoadFn<M>>,
+    events: ModelEvents,
+}
+
+impl<M: TreeModel> TreeTableModel<M> {
+    pub fn new(model: M) -> TreeTableModel<M> {
+        TreeTableModel {
+            model,
+            rows: Vec::new(),
+            dirty: true,
+            row_height: 20,
+            theme: Default::default(),
+            loader: None,
+            events: ModelEvents::new(),
+        }
+    }
+
+    pub fn with_row_height(mut self, row_height: u32) -> Self {
+        self.row_height = row_height;
+        self
+    }
+
+    /// Draw the first column with the colors and font of a theme, to match the table.
+    pub fn with_theme(mut self, theme: &TableTheme) -> Self {
+        self.theme = *theme;
+        self
+    }
+
+    /// Called before a node is expanded, to load its children on demand.
+    pub fn with_loader(mut self, loader: impl FnMut(&mut M, &M::Node) + Send + 'static) -> Self {
+        self.loader = Some(Box::new(loader));
+        self
+    }
+
+    /// List the visible nodes again, after the tree changed outside of the table.
+    pub fn refresh(&mut self) {
+        self.dirty = true;
+        self.events.reset();
+    }
+
+    /// Node shown in a row.
+    pub fn node(&mut self, row: usize) -> Option<&M::Node> {
+        self.update();
+        self.rows.get(row).map(|r| &r.node)
+    }
+
+    /// Expand or collapse the node in a row.  Returns false if the row has no children, or is already
+    /// expanded or collapsed.
+    pub fn set_expanded(&mut self, row: usize, expand: bool) -> bool {
+        self.update();
+        let node = match self.rows.get(row) {
+            Some(r) if r.has_children && r.expanded != expand => r.node.clone(),
+            _ => return false,
+        };
+        if expand {
+            if let Some(load) = self.loader.as_mut() {
+                load(&mut self.model, &node);
+            }
+        }
+        self.model.toggle(&node);
+        let before = self.rows.len();
+        self.flatten();
+        let after = self.rows.len();
+        let children = row + 1;
+        if after > before {
+            self.events
+                .rows_inserted(children..children + after - before);
+        } else if before > after {
+            self.events
+                .rows_removed(children..children + before - after);
+        }
+        // the triangle turned
+        self.events.rows_updated(row..row + 1);
+        true
+    }
+
+    fn update(&mut self) {
+        if self.dirty {
+            self.flatten();
+        }
+    }
+
+    /// List the visible nodes, depth first.
+    fn flatten(&mut self) {
+        let mut rows = Vec::new();
+        let mut stack: Vec<(M::Node, usize)> = self
+            .model
+            .roots()
+            .into_iter()
+            .rev()
+            .map(|node| (node, 0))
+            .collect();
+        while let Some((node, depth)) = stack.pop() {
+            let has_children = self.model.has_children(&node);
+            let expanded = has_children && self.model.is_expanded(&node);
+            if expanded {
+                let children = self.model.children(&node);
+                stack.extend(children.into_iter().rev().map(|child| (child, depth + 1)));
+            }
+            rows.push(TreeRow {
+                node,
+                depth,
+                has_children,
+                expanded,
+            });
+        }
+        self.rows = rows;
+        self.dirty = false;
+    }
+}
+
+impl<M: TreeModel> SimpleModel for TreeTableModel<M> {
+    fn row_info(&mut self) -> RowInfo {
+        self.update();
+        RowInfo {
+            count: self.rows.len(),
+            height: RowHeight::All(self.row_height),
+        }
+    }
+
+    fn column_info(&mut self) -> ColumnInfo {
+        self.model.column_info()
+    }
+
+    fn events(&mut self) -> Option<ModelEvents> {
+        Some(self.events.clone())
+    }
+
+    fn get_cell(&mut self, row: i32, col: i32) -> SimpleCell {
+        self.update();
+        let Some(r) = self.rows.get(row as usize) else {
+            return SimpleCell::None;
+        };
+        let cell = self.model.get_cell(&r.node, col);
+        if col != 0 {
+            return cell;
+        }
+        SimpleCell::Delegate(Box::new(TreeCell {
+            text: cell.to_text().unwrap_or_default(),
+            depth: r.depth,
+            has_children: r.has_children,
+            expanded: r.expanded,
+            bold: false,
+            theme: self.theme,
+        }))
+    }
+
+    fn row_key(&mut self, row: i32) -> Option<u64> {
+        self.update();
+        let node = self.rows.get(row as usize)?.node.clone();
+        self.model.node_key(&node)
+    }
+
+    /// Enter on a row expands or collapses it.
+    fn activate(&mut self, row: i32, _col: i32) -> bool {
+        self.update();
+        match self.rows.get(row as usize) {
+            Some(r) => {
+                let expand = !r.expanded;
+                self.set_expanded(row as usize, expand)
+            }
+            None => false,
+        }
+    }
+
+    fn expand(&mut self, row: i32, expand: bool) -> bool {
+        row >= 0 && self.set_expanded(row as usize, expand)
+    }
+}
+
+/// First column of a tree row, or of a group header: indentation, disclosure triangle and text.
+pub(crate) struct TreeCell {
+    pub text: String,
+    pub depth: usize,
+    pub has_children: bool,
+    pub expanded: bool,
+    pub bold: bool,
+    pub theme: TableTheme,
+}
+
+impl DrawDelegate for TreeCell {
+    fn draw(&self, _row: i32, _col: i32, x: i32, y: i32, w: i32, h: i32, selected: bool) {
+        set_draw_color(if selected {
+            self.theme.selected_foreground()
+        } else {
+            self.theme.foreground
+        });
+        let indent = INDENT * self.depth as i32;
+        if self.has_children {
+            let (cx, cy) = (x + indent + INDENT / 2, y + h / 2);
+            if self.expanded {
+                draw_polygon(cx - 4, cy - 2, cx + 4, cy - 2, cx, cy + 3);
+            } else {
+                draw_polygon(cx - 2, cy - 4, cx - 2, cy + 4, cx + 3, cy);
+            }
+        }
+        let text_x = x + indent + INDENT;
+        let style = CellStyle {
+            bold: self.bold,
+            ..Default::default()
+        };
+        draw::set_font(style.font_or(self.theme.font), self.theme.font_size);
+        draw_text2(&self.text, text_x, y, w - indent - INDENT, h, Align::Left);
+    }
+
+    fn text(&self) -> Option<String> {
+        Some(self.text.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::{Arc, Mutex},
+    };
+
+    use super::*;
+    use crate::model_events::ModelEvent;
+    use crate::simple_model::ColumnDetail;
+
+    /// Nodes are numbers, and 0 is the parent of the roots.
+    struct Tree {
+        children: HashMap<u32, Vec<u32>>,
+        expanded: HashSet<u32>,
+    }
+    impl TreeModel for Tree {
+        type Node = u32;
+        fn roots(&mut self) -> Vec<u32> {
+            self.children(&0)
+        }
+        fn children(&mut self, node: &u32) -> Vec<u32> {
+            self.children.get(node).cloned().unwrap_or_default()
+        }
+        fn is_expanded(&self, node: &u32) -> bool {
+            self.expanded.contains(node)
+        }
+        fn toggle(&mut self, node: &u32) {
+            if !self.expanded.remove(node) {
+                self.expanded.insert(*node);
+            }
+        }
+        fn column_info(&mut self) -> ColumnInfo {
+            ColumnInfo {
+                details: vec![ColumnDetail {
+                    header: "Node".to_string(),
+                    width: 100,
+                }],
+            }
+        }
+        fn get_cell(&mut self, node: &u32, _col: i32) -> SimpleCell {
+            SimpleCell::Int(*node as i64)
+        }
+        fn node_key(&mut self, node: &u32) -> Option<u64> {
+            Some(*node as u64)
+        }
+    }
+
+    /// 1 ─ 11 ─ 111
+    ///   └ 12
+    /// 2
+    fn tree() -> (TreeTableModel<Tree>, Arc<Mutex<Vec<ModelEvent>>>) {
+        let children = [(0, vec![1, 2]), (1, vec![11, 12]), (11, vec![111])];
+        let mut model = TreeTableModel::new(Tree {
+            children: children.into_iter().collect(),
+            expanded: HashSet::new(),
+        });
+        let events: Arc<Mutex<Vec<ModelEvent>>> = Default::default();
+        let log = events.clone();
+        model
+            .events()
+            .unwrap()
+            .subscribe(move |e| log.lock().unwrap().push(e.clone()));
+        (model, events)
+    }
+
+    fn visible(model: &mut TreeTableModel<Tree>) -> Vec<(u32, usize)> {
+        model.update();
+        model.rows.iter().map(|r| (r.node, r.depth)).collect()
+    }
+
+    #[test]
+    fn expand_and_collapse() {
+        let (mut model, events) = tree();
+        assert_eq!(visible(&mut model), vec![(1, 0), (2, 0)]);
+        assert!(model.set_expanded(0, true));
+        assert!(!model.set_expanded(0, true));
+        assert_eq!(visible(&mut model), vec![(1, 0), (11, 1), (12, 1), (2, 0)]);
+        assert!(model.expand(1, true));
+        assert_eq!(model.row_info().count, 5);
+        assert_eq!(model.node(2), Some(&111));
+        assert_eq!(model.row_key(4), Some(2));
+        // leaves can't expand
+        assert!(!model.set_expanded(2, true));
+        assert!(!model.activate(4, 0));
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ModelEvent::RowsInserted(1..3),
+                ModelEvent::RowsUpdated(0..1),
+                ModelEvent::RowsInserted(2..3),
+                ModelEvent::RowsUpdated(1..2),
+            ]
+        );
+        events.lock().unwrap().clear();
+        // collapsing hides the descendants, which stay expanded for next time
+        assert!(model.activate(0, 0));
+        assert_eq!(visible(&mut model), vec![(1, 0), (2, 0)]);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![ModelEvent::RowsRemoved(1..4), ModelEvent::RowsUpdated(0..1)]
+        );
+        model.set_expanded(0, true);
+        assert_eq!(model.row_info().count, 5);
+    }
+
+    #[test]
+    fn loader_runs_before_expanding() {
+        let (model, _) = tree();
+        let mut model = model.with_loader(|tree, node| {
+            tree.children.entry(*node).or_default().push(13);
+        });
+        model.set_expanded(0, true);
+        assert_eq!(
+            visible(&mut model),
+            vec![(1, 0), (11, 1), (12, 1), (13, 1), (2, 0)]
+        );
+        // children added outside of the table show after a refresh
+        assert!(!model.set_expanded(4, true));
+        model.model.children.insert(2, vec![21]);
+        model.refresh();
+        assert!(model.set_expanded(4, true));
+        assert_eq!(visible(&mut model)[4..], [(2, 0), (21, 1), (13, 1)]);
+    }
+
+    #[test]
+    fn first_column_is_indented() {
+        let (mut model, _) = tree();
+        model.set_expanded(0, true);
+        let cell = model.get_cell(1, 0);
+        assert!(matches!(cell, SimpleCell::Delegate(_)));
+        assert_eq!(cell.to_text().as_deref(), Some("11"));
+        assert!(matches!(model.get_cell(1, 1), SimpleCell::Int(11)));
+        assert!(matches!(model.get_cell(9, 0), SimpleCell::None));
+    }
+}
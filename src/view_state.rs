@@ -0,0 +1,451 @@
+use std::{collections::BTreeMap, ops::Range};
+
+use crate::{
+    column_view::ColumnView,
+    simple_model::{json_string, ColumnDetail, Order},
+};
+
+/// The user's adjustments to a table, so they can be saved and restored between runs.
+/// Columns are identified by header rather than index, so a saved state still applies after the
+/// model adds or removes columns.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableViewState {
+    /// headers in display order
+    pub order: Vec<String>,
+    /// widths set by the user, by header
+    pub widths: BTreeMap<String, u32>,
+    /// headers of hidden columns
+    pub hidden: Vec<String>,
+    /// sort keys, highest priority first
+    pub sort: Vec<(String, Order)>,
+    /// quick filter text
+    pub filter: String,
+    /// selected rows, as sorted ranges
+    pub selection: Vec<Range<usize>>,
+    /// first visible row
+    pub top_row: usize,
+    /// header of the first visible column
+    pub left_col: Option<String>,
+}
+
+impl TableViewState {
+    /// Capture the column order, widths, hidden columns and sort keys.
+    pub(crate) fn save_columns(
+        &mut self,
+        columns: &ColumnView,
+        details: &[ColumnDetail],
+        sort: &[(usize, Order)],
+    ) {
+        let header = |c: &usize| details[*c].header.clone();
+        self.order = columns.order().iter().map(header).collect();
+        self.widths = columns
+            .widths()
+            .iter()
+            .filter(|(c, _)| **c < details.len())
+            .map(|(c, w)| (header(c), *w))
+            .collect();
+        self.hidden = columns
+            .order()
+            .iter()
+            .filter(|c| columns.is_hidden(**c))
+            .map(header)
+            .collect();
+        self.sort = sort
+            .iter()
+            .filter(|(c, _)| *c < details.len())
+            .map(|(c, o)| (header(c), *o))
+            .collect();
+    }
+
+    /// Apply the column order, widths and hidden columns, and return the sort keys.
+    /// Headers the model no longer has are ignored.
+    pub(crate) fn restore_columns(
+        &self,
+        columns: &mut ColumnView,
+        details: &[ColumnDetail],
+    ) -> Vec<(usize, Order)> {
+        let col = |header: &String| details.iter().position(|d| d.header == *header);
+        columns.update(details.len());
+        columns.set_order(self.order.iter().filter_map(col).collect());
+        columns.clear_widths();
+        for (header, width) in &self.widths {
+            if let Some(c) = col(header) {
+                columns.set_width(c, *width);
+            }
+        }
+        for (c, detail) in details.iter().enumerate() {
+            columns.set_hidden(c, self.hidden.contains(&detail.header));
+        }
+        self.sort
+            .iter()
+            .filter_map(|(header, order)| col(header).map(|c| (c, *order)))
+            .collect()
+    }
+
+    /// Model column for the saved first visible column.
+    pub(crate) fn left_col(&self, details: &[ColumnDetail]) -> Option<usize> {
+        let header = self.left_col.as_ref()?;
+        details.iter().position(|d| d.header == *header)
+    }
+
+    pub fn to_json(&self) -> String {
+        let strings = |v: &[String]| {
+            let v: Vec<String> = v.iter().map(|s| json_string(s)).collect();
+            format!("[{}]", v.join(", "))
+        };
+        let widths: Vec<String> = self
+            .widths
+            .iter()
+            .map(|(h, w)| format!("{}: {}", json_string(h), w))
+            .collect();
+        let sort: Vec<String> = self
+            .sort
+            .iter()
+            .map(|(h, o)| format!("[{}, {}]", json_string(h), json_string(order_name(*o))))
+            .collect();
+        let selection: Vec<String> = self
+            .selection
+            .iter()
+            .map(|r| format!("[{}, {}]", r.start, r.end))
+            .collect();
+        let left_col = match &self.left_col {
+            Some(h) => json_string(h),
+            None => "null".to_string(),
+        };
+        let fields = [
+            format!("\"order\": {}", strings(&self.order)),
+            format!("\"widths\": {{{}}}", widths.join(", ")),
+            format!("\"hidden\": {}", strings(&self.hidden)),
+            format!("\"sort\": [{}]", sort.join(", ")),
+            format!("\"filter\": {}", json_string(&self.filter)),
+            format!("\"selection\": [{}]", selection.join(", ")),
+            format!("\"top_row\": {}", self.top_row),
+            format!("\"left_col\": {}", left_col),
+        ];
+        format!("{{\n  {}\n}}\n", fields.join(",\n  "))
+    }
+
+    /// Parse a state written by `to_json`.  Missing fields keep their defaults, and unknown fields, such as
+    /// those written by a newer version, are ignored.
+    pub fn from_json(json: &str) -> Result<TableViewState, String> {
+        let mut parser = Parser {
+            chars: json.chars().peekable(),
+        };
+        let value = parser.value()?;
+        parser.skip_ws();
+        if parser.chars.peek().is_some() {
+            return Err("Unexpected text after state".to_string());
+        }
+        let Json::Object(fields) = value else {
+            return Err("State must be an object".to_string());
+        };
+        let mut state = TableViewState::default();
+        for (name, value) in fields {
+            match (name.as_str(), value) {
+                ("order", v) => state.order = v.strings()?,
+                ("widths", Json::Object(widths)) => {
+                    for (h, w) in widths {
+                        state.widths.insert(h, w.number()? as u32);
+                    }
+                }
+                ("hidden", v) => state.hidden = v.strings()?,
+                ("sort", Json::Array(keys)) => {
+                    for key in keys {
+                        match key.strings()?.as_slice() {
+                            [h, o] => state.sort.push((h.clone(), parse_order(o)?)),
+                            _ => return Err("Sort key must be [header, order]".to_string()),
+                        }
+                    }
+                }
+                ("filter", Json::String(s)) => state.filter = s,
+                ("selection", Json::Null) => state.selection.clear(),
+                // a single [start, end] range, as written by earlier versions
+                ("selection", Json::Array(r)) if matches!(r.first(), Some(Json::Number(_))) => {
+                    state.selection = vec![range(&r)?];
+                }
+                ("selection", Json::Array(ranges)) => {
+                    for r in ranges {
+                        match r {
+                            Json::Array(r) => state.selection.push(range(&r)?),
+                            _ => return Err("Selection must be a list of [start, end]".to_string()),
+                        }
+                    }
+                }
+                ("top_row", v) => state.top_row = v.number()? as usize,
+                ("left_col", Json::Null) => state.left_col = None,
+                ("left_col", Json::String(s)) => state.left_col = Some(s),
+                (name, _) if FIELDS.contains(&name) => {
+                    return Err(format!("Unexpected value for {}", name))
+                }
+                _ => {}
+            }
+        }
+        Ok(state)
+    }
+}
+
+/// Fields written by `to_json`.
+const FIELDS: [&str; 8] = [
+    "order",
+    "widths",
+    "hidden",
+    "sort",
+    "filter",
+    "selection",
+    "top_row",
+    "left_col",
+];
+
+/// Rows from a [start, end] array.
+fn range(values: &[Json]) -> Result<Range<usize>, String> {
+    match values {
+        [start, end] => Ok(start.number()? as usize..end.number()? as usize),
+        _ => Err("Selection range must be [start, end]".to_string()),
+    }
+}
+
+fn order_name(order: Order) -> &'static str {
+    match order {
+        Order::Ascending => "ascending",
+        Order::Descending => "descending",
+        Order::None => "none",
+    }
+}
+
+fn parse_order(name: &str) -> Result<Order, String> {
+    match name {
+        "ascending" => Ok(Order::Ascending),
+        "descending" => Ok(Order::Descending),
+        "none" => Ok(Order::None),
+        _ => Err(format!("Unknown sort order {}", name)),
+    }
+}
+
+/// Just enough JSON to read back a state.
+enum Json {
+    Null,
+    Bool,
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn number(&self) -> Result<f64, String> {
+        match self {
+            Json::Number(n) if *n >= 0.0 => Ok(*n),
+            _ => Err("Expected a positive number".to_string()),
+        }
+    }
+
+    fn strings(self) -> Result<Vec<String>, String> {
+        match self {
+            Json::Array(values) => values
+                .into_iter()
+                .map(|v| match v {
+                    Json::String(s) => Ok(s),
+                    _ => Err("Expected a string".to_string()),
+                })
+                .collect(),
+            _ => Err("Expected an array of strings".to_string()),
+        }
+    }
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_ws(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_ws();
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("Expected '{}', found '{}'", expected, c)),
+            None => Err(format!("Expected '{}'", expected)),
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_ws();
+        match self.chars.peek() {
+            Some('{') => {
+                self.chars.next();
+                let mut fields = Vec::new();
+                self.skip_ws();
+                if self.chars.next_if_eq(&'}').is_some() {
+                    return Ok(Json::Object(fields));
+                }
+                loop {
+                    self.skip_ws();
+                    let name = self.string()?;
+                    self.expect(':')?;
+                    fields.push((name, self.value()?));
+                    self.skip_ws();
+                    match self.chars.next() {
+                        Some(',') => {}
+                        Some('}') => return Ok(Json::Object(fields)),
+                        _ => return Err("Expected ',' or '}'".to_string()),
+                    }
+                }
+            }
+            Some('[') => {
+                self.chars.next();
+                let mut values = Vec::new();
+                self.skip_ws();
+                if self.chars.next_if_eq(&']').is_some() {
+                    return Ok(Json::Array(values));
+                }
+                loop {
+                    values.push(self.value()?);
+                    self.skip_ws();
+                    match self.chars.next() {
+                        Some(',') => {}
+                        Some(']') => return Ok(Json::Array(values)),
+                        _ => return Err("Expected ',' or ']'".to_string()),
+                    }
+                }
+            }
+            Some('"') => Ok(Json::String(self.string()?)),
+            Some(c) if *c == '-' || c.is_ascii_digit() => {
+                let mut number = String::new();
+                while let Some(c) = self
+                    .chars
+                    .next_if(|c| c.is_ascii_digit() || "+-.eE".contains(*c))
+                {
+                    number.push(c);
+                }
+                number
+                    .parse()
+                    .map(Json::Number)
+                    .map_err(|_| format!("Bad number {}", number))
+            }
+            Some(_) => {
+                let mut word = String::new();
+                while let Some(c) = self.chars.next_if(|c| c.is_ascii_alphabetic()) {
+                    word.push(c);
+                }
+                match word.as_str() {
+                    "null" => Ok(Json::Null),
+                    "true" | "false" => Ok(Json::Bool),
+                    _ => Err(format!("Unexpected '{}'", word)),
+                }
+            }
+            None => Err("Unexpected end of state".to_string()),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.chars.next() {
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('u') => {
+                        let mut code = self.hex4()?;
+                        // characters outside the BMP are escaped as a surrogate pair
+                        if (0xD800..0xDC00).contains(&code) {
+                            if self.chars.next() != Some('\\') || self.chars.next() != Some('u') {
+                                return Err("Expected a low surrogate".to_string());
+                            }
+                            let low = self.hex4()?;
+                            if !(0xDC00..0xE000).contains(&low) {
+                                return Err(format!("Bad low surrogate \\u{:04x}", low));
+                            }
+                            code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                        }
+                        let c = char::from_u32(code)
+                            .ok_or_else(|| format!("Bad escape \\u{:04x}", code))?;
+                        s.push(c);
+                    }
+                    Some(c) => s.push(c),
+                    None => return Err("Unterminated string".to_string()),
+                },
+                Some(c) => s.push(c),
+                None => return Err("Unterminated string".to_string()),
+            }
+        }
+    }
+
+    /// The four hex digits of a \u escape.
+    fn hex4(&mut self) -> Result<u32, String> {
+        let hex: String = self.chars.by_ref().take(4).collect();
+        match u32::from_str_radix(&hex, 16) {
+            Ok(code) if hex.len() == 4 => Ok(code),
+            _ => Err(format!("Bad escape \\u{}", hex)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> TableViewState {
+        TableViewState {
+            order: vec![
+                "b".to_string(),
+                "Name \"q\"".to_string(),
+                "Δ 🦀".to_string(),
+            ],
+            widths: [("b".to_string(), 40), ("tab\there".to_string(), 90)]
+                .into_iter()
+                .collect(),
+            hidden: vec!["b".to_string()],
+            sort: vec![
+                ("Name \"q\"".to_string(), Order::Descending),
+                ("b".to_string(), Order::Ascending),
+            ],
+            filter: "back\\slash\nline\u{1}".to_string(),
+            selection: vec![1..2, 3..7],
+            top_row: 12,
+            left_col: Some("Δ 🦀".to_string()),
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let state = state();
+        assert_eq!(TableViewState::from_json(&state.to_json()), Ok(state));
+        let empty = TableViewState::default();
+        assert_eq!(TableViewState::from_json(&empty.to_json()), Ok(empty));
+    }
+
+    #[test]
+    fn unknown_fields_are_ignored() {
+        let json = r#"{"top_row": 2, "zoom": {"level": [1.5, true, null]}, "filter": "x"}"#;
+        let state = TableViewState::from_json(json).unwrap();
+        assert_eq!(state.top_row, 2);
+        assert_eq!(state.filter, "x");
+        assert!(TableViewState::from_json(r#"{"filter": 3}"#).is_err());
+    }
+
+    #[test]
+    fn surrogate_pairs() {
+        let state = TableViewState::from_json(r#"{"order": ["\ud83e\udd80 \u0394"]}"#).unwrap();
+        assert_eq!(state.order, vec!["🦀 Δ"]);
+        assert!(TableViewState::from_json(r#"{"order": ["\ud83e"]}"#).is_err());
+        assert!(TableViewState::from_json(r#"{"order": ["\ud83e\u0041"]}"#).is_err());
+        assert!(TableViewState::from_json(r#"{"order": ["\u00"]}"#).is_err());
+    }
+
+    #[test]
+    fn single_range_selection() {
+        let state = TableViewState::from_json(r#"{"selection": [3, 7]}"#).unwrap();
+        assert_eq!(state.selection, vec![3..7]);
+        let state = TableViewState::from_json(r#"{"selection": null}"#).unwrap();
+        assert!(state.selection.is_empty());
+        assert!(TableViewState::from_json(r#"{"selection": [[3]]}"#).is_err());
+    }
+}